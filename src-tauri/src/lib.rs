@@ -1,8 +1,22 @@
 use anyhow::Context;
-use todo::{persist::ActualTodosDB, Todo, Todos};
+use tauri::{AppHandle, Manager};
+use todo::{persist::ActualTodosDB, Stats, Todo, Todos, TodosWithStats};
 
 type TodosState = Todos<ActualTodosDB>;
 
+/// Fired with the whole list whenever the todos are saved.
+const TODOS_CHANGED_EVENT: &str = "todos-changed";
+
+/// Have every save of `todos` emit [`TODOS_CHANGED_EVENT`], so the frontend can keep up with
+/// changes it didn't ask for.
+fn emit_changes(app: AppHandle, todos: &TodosState) {
+    todos.on_flush(move |all| {
+        if let Err(err) = app.emit(TODOS_CHANGED_EVENT, all.to_vec()) {
+            eprintln!("[WARN] failed to emit {TODOS_CHANGED_EVENT}: {err}");
+        }
+    });
+}
+
 fn initial_todos_state() -> TodosState {
     Todos::load_up_with_persistor()
 }
@@ -21,22 +35,45 @@ fn load(todos: tauri::State<'_, TodosState>) -> TodosCommandResult {
         .into_command_result()
 }
 
+/// Like `load`, with the counts for the list that's sent, so they can't disagree.
+#[tauri::command]
+fn load_with_stats(todos: tauri::State<'_, TodosState>) -> CommandResult<TodosWithStats> {
+    todos
+        .reload()
+        .context("failed to reload todos")
+        .into_command_result()?;
+
+    todos
+        .get_all_with_stats()
+        .context("failed to fetch all todos")
+        .into_command_result()
+}
+
 #[tauri::command]
 fn add(todo: String, todos: tauri::State<'_, TodosState>) -> TodosCommandResult {
     todos.add_message(&todo).into_command_result()?;
     todos.flush().into_command_result()
 }
 
+/// Soft delete: marks the todo done, leaving it in the list. Undoes that if it's already done,
+/// unless `done_toggles` is configured off.
 #[tauri::command]
 fn remove(id: String, todos: tauri::State<'_, TodosState>) -> TodosCommandResult {
-    todos
-        .mark_done(&id)
+    let cfg = todo::config::load_config_or_default();
+    let marked = if cfg.done_toggles {
+        todos.mark_done(&id)
+    } else {
+        todos.set_done(&id, true)
+    };
+
+    marked
         .context("failed to remove (mark done) a todo")
         .into_command_result()?;
 
     todos.flush().into_command_result()
 }
 
+/// Hard delete: removes the todo from the list entirely.
 #[tauri::command]
 fn delete(id: String, todos: tauri::State<'_, TodosState>) -> TodosCommandResult {
     todos
@@ -91,20 +128,82 @@ fn move_below(
     todos.get_all().into_command_result()
 }
 
+#[tauri::command]
+fn reorder(id: String, index: usize, todos: tauri::State<'_, TodosState>) -> TodosCommandResult {
+    todos
+        .reorder(&id, index)
+        .context("failed to move a todo to a new position")
+        .into_command_result()?;
+
+    todos.get_all().into_command_result()
+}
+
+/// Tidy up: move the done todos below the pending ones.
+#[tauri::command]
+fn sink_done(todos: tauri::State<'_, TodosState>) -> TodosCommandResult {
+    todos
+        .sink_done()
+        .context("failed to move the done todos to the bottom")
+        .into_command_result()?;
+
+    todos.get_all().into_command_result()
+}
+
+/// Make the list's order match `ids`, as it's been arranged in the frontend.
+#[tauri::command]
+fn set_order(ids: Vec<String>, todos: tauri::State<'_, TodosState>) -> TodosCommandResult {
+    todos
+        .set_order(&ids)
+        .context("failed to reorder the todos")
+        .into_command_result()?;
+
+    todos.get_all().into_command_result()
+}
+
+#[tauri::command]
+fn move_many_to_top(ids: Vec<String>, todos: tauri::State<'_, TodosState>) -> TodosCommandResult {
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+    todos
+        .move_many_to_top(&ids)
+        .context("failed to move todos to the top")
+        .into_command_result()?;
+
+    todos.get_all().into_command_result()
+}
+
+/// Counts and the oldest and newest pending todos, e.g. for a summary header.
+#[tauri::command]
+fn stats(todos: tauri::State<'_, TodosState>) -> CommandResult<Stats> {
+    todos
+        .stats()
+        .context("failed to get the todo stats")
+        .into_command_result()
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(initial_todos_state())
+        .setup(|app| {
+            emit_changes(app.handle().clone(), &app.state::<TodosState>());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             load,
+            load_with_stats,
             add,
             remove,
             delete,
             move_up,
             move_down,
             remove_done,
-            move_below
+            move_below,
+            reorder,
+            move_many_to_top,
+            set_order,
+            sink_done,
+            stats
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,17 +1,25 @@
 use crate::{Todo, TodoID};
 
 pub trait TodoCollection {
-    fn add_message(&mut self, message: &str) -> anyhow::Result<Todo>;
-
     fn add_todo(&mut self, todo: Todo);
 
+    /// Add a todo item at `index`, clamped to the end of the list, unless one with its id is
+    /// already in it. Pinned todos stay on top regardless.
+    fn insert_todo(&mut self, index: usize, todo: Todo);
+
     fn remove(&mut self, id: &str) -> anyhow::Result<()>;
 
     fn contains(&self, id: &TodoID) -> bool;
 
+    fn get(&self, id: &str) -> anyhow::Result<Todo>;
+
     fn len(&self) -> usize;
 
-    fn mark_done(&mut self, id: &str) -> anyhow::Result<()>;
+    /// Toggle the done state of a todo item, returning whether it changed.
+    fn mark_done(&mut self, id: &str) -> anyhow::Result<bool>;
+
+    /// Set the done state of a todo item, returning whether it changed.
+    fn set_done(&mut self, id: &str, done: bool) -> anyhow::Result<bool>;
 
     fn remove_done(&mut self);
 
@@ -22,7 +30,31 @@ pub trait TodoCollection {
     /// Move a todo item to be directly below another.
     fn move_below(&mut self, id: &str, target_id: &str) -> anyhow::Result<()>;
 
+    /// Replace the todo item with the same id, keeping its place in the list.
+    fn update(&mut self, todo: Todo) -> anyhow::Result<()>;
+
+    /// Pin or unpin a todo item. Returns whether it's pinned now.
+    fn toggle_pin(&mut self, id: &str) -> anyhow::Result<bool>;
+
+    /// Set a todo item's priority, returning whether it changed.
+    fn set_priority(&mut self, id: &str, priority: u8) -> anyhow::Result<bool>;
+
+    /// Move a todo item to end up at `to_index`, clamped to the end of the list.
+    fn reorder(&mut self, id: &str, to_index: usize) -> anyhow::Result<()>;
+
+    /// Move todo items to the top together, keeping the order they were in.
+    fn move_many_to_top(&mut self, ids: &[&str]) -> anyhow::Result<()>;
+
+    /// Move todo items to the bottom together, keeping the order they were in.
+    fn move_many_to_bottom(&mut self, ids: &[&str]) -> anyhow::Result<()>;
+
+    /// Put the todo items in the order of `ids`, which must name each of them exactly once.
+    fn set_order(&mut self, ids: &[String]) -> anyhow::Result<()>;
+
     fn get_all(&self) -> Vec<Todo>;
+
+    /// The todo items in order, borrowed rather than cloned like with [`Self::get_all`].
+    fn iter(&self) -> impl Iterator<Item = &Todo>;
 }
 
 pub mod array {
@@ -40,45 +72,92 @@ pub mod array {
             Self { list: vec![] }
         }
 
+        /// Finds by the whole id, or failing that, by a prefix of it, like a short id.
         fn find_index(&self, id: &str) -> anyhow::Result<usize> {
-            let idx = self
+            if let Some(idx) = self.list.iter().position(|t| *t.id.0 == *id) {
+                return Ok(idx);
+            }
+
+            let mut matches = self
                 .list
                 .iter()
                 .enumerate()
-                .find(|(_, t)| t.id == TodoID(id.into()))
-                .context("didn't find a todo by the id provided")?
-                .0;
+                .filter(|(_, t)| !id.is_empty() && t.id.0.starts_with(id));
+
+            let (idx, _) = matches
+                .next()
+                .context("didn't find a todo by the id provided")?;
+
+            if matches.next().is_some() {
+                return Err(anyhow!(
+                    "more than one todo's id starts with: {}; give more of the id",
+                    id
+                ));
+            }
 
             Ok(idx)
         }
+
+        /// Take out the todo items with these ids, in the order they're in the list.
+        fn take_many(&mut self, ids: &[&str]) -> anyhow::Result<Vec<Todo>> {
+            let mut indices = ids
+                .iter()
+                .map(|id| self.find_index(id))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            indices.sort_unstable();
+            indices.dedup();
+
+            let mut taken = Vec::with_capacity(indices.len());
+            for idx in indices.into_iter().rev() {
+                let mut todo = self.list.remove(idx);
+                todo.touch();
+                taken.push(todo);
+            }
+            taken.reverse();
+
+            Ok(taken)
+        }
+
+        /// Make every todo's `order` match its position, after moving pinned todos to the top.
+        fn renumber(&mut self) {
+            self.list.sort_by_key(|t| !t.pinned);
+            for (idx, todo) in self.list.iter_mut().enumerate() {
+                todo.order = idx as u32;
+            }
+        }
     }
 
     impl From<Vec<Todo>> for TodoArrayList {
-        fn from(value: Vec<Todo>) -> Self {
-            Self { list: value }
+        /// Sorts by `order`, keeping the given sequence for ties (e.g. from files saved before
+        /// `order` existed).
+        fn from(mut value: Vec<Todo>) -> Self {
+            value.iter_mut().for_each(Todo::backfill_updated_at);
+            value.sort_by_key(|t| t.order);
+            let mut list = Self { list: value };
+            list.renumber();
+            list
         }
     }
 
     impl super::TodoCollection for TodoArrayList {
-        fn add_message(&mut self, message: &str) -> anyhow::Result<Todo> {
-            let todo = Todo::new(message.to_string());
-
-            self.add_todo(todo.clone());
-
-            Ok(todo)
+        fn add_todo(&mut self, todo: Todo) {
+            self.insert_todo(self.len(), todo);
         }
 
-        fn add_todo(&mut self, todo: Todo) {
+        fn insert_todo(&mut self, index: usize, mut todo: Todo) {
             if self.contains(&todo.id) {
                 return;
             }
-            self.list.push(todo);
+            todo.backfill_updated_at();
+            self.list.insert(index.min(self.len()), todo);
+            self.renumber();
         }
 
         fn remove(&mut self, id: &str) -> anyhow::Result<()> {
             let index = self.find_index(id)?;
 
             self.list.remove(index);
+            self.renumber();
 
             Ok(())
         }
@@ -87,36 +166,54 @@ pub mod array {
             return self.list.iter().any(|i| i.id == *id);
         }
 
+        fn get(&self, id: &str) -> anyhow::Result<Todo> {
+            let idx = self.find_index(id)?;
+
+            Ok(self.list[idx].clone())
+        }
+
         fn len(&self) -> usize {
             self.list.len()
         }
 
-        fn mark_done(&mut self, id: &str) -> anyhow::Result<()> {
+        fn mark_done(&mut self, id: &str) -> anyhow::Result<bool> {
             let idx = self.find_index(id)?;
+            let done = !self.list[idx].done;
 
-            let todo = self.list.get_mut(idx);
+            self.set_done(id, done)
+        }
+
+        fn set_done(&mut self, id: &str, done: bool) -> anyhow::Result<bool> {
+            let idx = self.find_index(id)?;
 
-            if let Some(todo) = todo {
-                todo.done = !todo.done;
+            let todo = &mut self.list[idx];
+            let changed = todo.done != done;
+            if changed {
+                todo.done = done;
+                todo.touch();
             }
 
-            Ok(())
+            Ok(changed)
         }
 
         fn remove_done(&mut self) {
             let copy = self.get_all();
             self.list = copy.iter().filter(|t| !t.done).cloned().collect();
+            self.renumber();
         }
 
         fn move_up(&mut self, id: String) -> anyhow::Result<()> {
             let idx = self.find_index(&id)?;
 
-            if idx < self.len() {
-                let curr = self.list[idx].clone();
+            // already at the top otherwise
+            if idx > 0 {
+                let mut curr = self.list[idx].clone();
+                curr.touch();
                 let temp = self.list[idx - 1].clone();
 
                 self.list[idx] = temp;
                 self.list[idx - 1] = curr;
+                self.renumber();
             }
 
             Ok(())
@@ -125,12 +222,15 @@ pub mod array {
         fn move_down(&mut self, id: String) -> anyhow::Result<()> {
             let idx = self.find_index(&id)?;
 
-            if idx < self.len() {
-                let curr = self.list[idx].clone();
+            // already at the bottom otherwise
+            if idx + 1 < self.len() {
+                let mut curr = self.list[idx].clone();
+                curr.touch();
                 let temp = self.list[idx + 1].clone();
 
                 self.list[idx] = temp;
                 self.list[idx + 1] = curr;
+                self.renumber();
             }
 
             Ok(())
@@ -138,21 +238,31 @@ pub mod array {
 
         /// Move a todo item to be directly below another.
         fn move_below(&mut self, id: &str, target_id: &str) -> anyhow::Result<()> {
-            // remember here that todos are added to the front of the list
-            // so 0..len is from most newest to oldest, top to bottom
-            // so i + 1 is below i
-
-            let idx = self.find_index(id)?;
-            let target_idx = self.find_index(target_id)?;
+            // 0..len is top to bottom, so i + 1 is below i
+
+            // look for both before failing, to say which is missing
+            let (idx, target_idx) = match (self.find_index(id), self.find_index(target_id)) {
+                (Ok(idx), Ok(target_idx)) => (idx, target_idx),
+                (Err(_), Err(_)) => {
+                    return Err(anyhow!(
+                        "neither the todo to move ({id}) nor the one to move it below ({target_id}) were found"
+                    ));
+                }
+                (Err(err), Ok(_)) => {
+                    return Err(err.context(format!("failed to find the todo to move: {id}")));
+                }
+                (Ok(_), Err(err)) => {
+                    return Err(err.context(format!(
+                        "failed to find the todo to move below: {target_id}"
+                    )));
+                }
+            };
             let below_target_idx = target_idx + 1;
 
-            // wouldn't make a difference if todo is own target or already below target
-            if idx == target_idx {
-                return Err(anyhow!("[INFO] noop: won't move a todo item below itself"));
-            }
-
-            if idx == below_target_idx {
-                return Err(anyhow!("[INFO] noop: todo is already below target"));
+            // wouldn't make a difference if todo is own target or already below target,
+            // which isn't worth failing over
+            if idx == target_idx || idx == below_target_idx {
+                return Ok(());
             }
 
             let size = self.len();
@@ -163,7 +273,8 @@ pub mod array {
                 ));
             }
 
-            let source = self.list[idx].clone();
+            let mut source = self.list[idx].clone();
+            source.touch();
 
             if idx < target_idx {
                 self.list.remove(idx);
@@ -173,12 +284,130 @@ pub mod array {
                 self.list.insert(below_target_idx, source);
             }
 
+            self.renumber();
+
+            Ok(())
+        }
+
+        fn update(&mut self, todo: Todo) -> anyhow::Result<()> {
+            let idx = self.find_index(&todo.id.0)?;
+            let order = self.list[idx].order;
+            self.list[idx] = Todo { order, ..todo };
+            self.renumber();
+
+            Ok(())
+        }
+
+        fn toggle_pin(&mut self, id: &str) -> anyhow::Result<bool> {
+            let idx = self.find_index(id)?;
+            let todo = &mut self.list[idx];
+            todo.pinned = !todo.pinned;
+            todo.touch();
+            let pinned = todo.pinned;
+
+            if pinned {
+                // to the bottom of the pinned ones, rather than wherever it happens to be
+                let todo = self.list.remove(idx);
+                let last_pinned = self.list.iter().take_while(|t| t.pinned).count();
+                self.list.insert(last_pinned, todo);
+            }
+
+            self.renumber();
+
+            Ok(pinned)
+        }
+
+        fn set_priority(&mut self, id: &str, priority: u8) -> anyhow::Result<bool> {
+            let idx = self.find_index(id)?;
+
+            let todo = &mut self.list[idx];
+            let changed = todo.priority != priority;
+            if changed {
+                todo.priority = priority;
+                todo.touch();
+            }
+
+            Ok(changed)
+        }
+
+        fn reorder(&mut self, id: &str, to_index: usize) -> anyhow::Result<()> {
+            let idx = self.find_index(id)?;
+
+            let mut source = self.list.remove(idx);
+            source.touch();
+            let to_index = to_index.min(self.len());
+            self.list.insert(to_index, source);
+
+            self.renumber();
+
+            Ok(())
+        }
+
+        fn move_many_to_top(&mut self, ids: &[&str]) -> anyhow::Result<()> {
+            let mut taken = self.take_many(ids)?;
+            taken.append(&mut self.list);
+            self.list = taken;
+
+            self.renumber();
+
+            Ok(())
+        }
+
+        fn move_many_to_bottom(&mut self, ids: &[&str]) -> anyhow::Result<()> {
+            let mut taken = self.take_many(ids)?;
+            self.list.append(&mut taken);
+
+            self.renumber();
+
+            Ok(())
+        }
+
+        fn set_order(&mut self, ids: &[String]) -> anyhow::Result<()> {
+            if ids.len() != self.len() {
+                return Err(anyhow!(
+                    "got {} ids to order {} todos by; every todo has to be given once",
+                    ids.len(),
+                    self.len()
+                ));
+            }
+
+            let mut indices = Vec::with_capacity(ids.len());
+            for id in ids {
+                let idx = self
+                    .find_index(id)
+                    .with_context(|| format!("failed to find the todo to order: {id}"))?;
+                if indices.contains(&idx) {
+                    return Err(anyhow!(
+                        "the todo {id} was given more than once to order by"
+                    ));
+                }
+                indices.push(idx);
+            }
+
+            let mut old: Vec<Option<Todo>> = std::mem::take(&mut self.list)
+                .into_iter()
+                .map(Some)
+                .collect();
+            for (position, idx) in indices.into_iter().enumerate() {
+                let mut todo = old[idx].take().expect("indices are unique");
+                if position != idx {
+                    todo.touch();
+                }
+                self.list.push(todo);
+            }
+
+            self.renumber();
+
             Ok(())
         }
 
         fn get_all(&self) -> Vec<Todo> {
             self.list.clone()
         }
+
+        fn iter(&self) -> impl Iterator<Item = &Todo> {
+            self.list.iter()
+        }
     }
 }
 
@@ -1,32 +1,243 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Once, OnceLock},
+};
+
 use anyhow::Context;
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 const APP_NAME: &str = "mynd";
 
+/// Where to read and write the config instead of the default location, e.g. from `--config`.
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
 #[derive(ValueEnum, Clone, Debug, Serialize, Deserialize)]
 pub enum SaveFileFormat {
     Json,
     Binary,
 }
 
-#[derive(Serialize, Deserialize)]
+/// How `ls` orders todos when not told otherwise.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultSort {
+    /// Most urgent first, then oldest first.
+    #[default]
+    Smart,
+    /// The list's own order.
+    Manual,
+    /// Oldest first.
+    Created,
+}
+
+/// Where new todos go in the list.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InsertAt {
+    /// Above the others, below any pinned ones.
+    Top,
+    /// Below the others.
+    #[default]
+    Bottom,
+}
+
+/// Colors for `ls`, by name, e.g. "blue" or "bright red". Unset or unknown ones are left as they
+/// are by default.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub pending: Option<String>,
+    pub done: Option<String>,
+    pub id: Option<String>,
+    pub time: Option<String>,
+}
+
+/// Which delimiters make a multiline todo in the todo language.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockDelimiters {
+    /// `todo { ... }`
+    pub braces: bool,
+    /// `todo <<END ... END`, with any word for `END`, for text that has braces in it.
+    pub heredoc: bool,
+}
+
+impl Default for BlockDelimiters {
+    fn default() -> Self {
+        Self {
+            braces: true,
+            heredoc: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MyndConfig {
     pub save_file_format: SaveFileFormat,
+    /// Editor to open the todo list with, when no `--editor` is given.
+    pub editor: Option<String>,
+    /// Save binary files by appending to a journal, instead of rewriting them every time.
+    pub journal: bool,
+    /// Gzip the save file.
+    pub compress: bool,
+    /// Words that start a todo in the todo language. The first is used when writing todos out.
+    pub keywords: Vec<String>,
+    /// Remove done todos at the end of every command that changes the todos. They're gone for
+    /// good; there's no archive to move them into.
+    pub auto_prune_done: bool,
+    /// How `ls` orders todos when no `--sort` is given.
+    pub default_sort: DefaultSort,
+    /// Whether `done` on a todo that's already done undoes it, instead of leaving it done.
+    pub done_toggles: bool,
+    /// Let todos have the same message as others, instead of adding one being a noop.
+    pub allow_duplicates: bool,
+    /// Keep the relative indentation of lines in multiline todos, e.g. for code, instead of
+    /// trimming it.
+    pub preserve_indent: bool,
+    /// Colors for `ls` output.
+    pub theme: Theme,
+    /// Which delimiters make a multiline todo in the todo language.
+    pub block_delimiters: BlockDelimiters,
+    /// Ask before deleting more than this many todos at once, unless given `--yes`.
+    pub confirm_delete_over: usize,
+    /// Where added todos go in the list.
+    pub insert_at: InsertAt,
+    /// How many columns a tab takes up in the positions `check` and `lex` report. The language
+    /// server always counts a tab as one, since LSP positions count characters, not columns.
+    pub tab_width: u32,
 }
 
 impl Default for MyndConfig {
     fn default() -> Self {
         Self {
             save_file_format: SaveFileFormat::Binary,
+            editor: None,
+            journal: false,
+            compress: false,
+            keywords: vec!["todo".to_string()],
+            auto_prune_done: false,
+            default_sort: DefaultSort::default(),
+            done_toggles: true,
+            allow_duplicates: false,
+            preserve_indent: false,
+            theme: Theme::default(),
+            block_delimiters: BlockDelimiters::default(),
+            confirm_delete_over: 5,
+            insert_at: InsertAt::default(),
+            tab_width: 1,
         }
     }
 }
 
+/// Use the config file at `path` from here on, for the rest of the process. Only the first call
+/// has any effect.
+pub fn use_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH.set(path);
+}
+
 pub fn load_config() -> anyhow::Result<MyndConfig> {
+    if let Some(path) = CONFIG_PATH.get() {
+        return load_config_from(path);
+    }
     confy::load::<MyndConfig>(APP_NAME, None).context("failed to load cli configs")
 }
 
+/// The config, or the defaults if it can't be loaded, with a warning. Quietly using the defaults
+/// could mean using another save file, and looking like the todos are gone.
+pub fn load_config_or_default() -> MyndConfig {
+    or_default(load_config())
+}
+
+fn or_default(loaded: anyhow::Result<MyndConfig>) -> MyndConfig {
+    // it's loaded more than once in a command
+    static WARNED: Once = Once::new();
+
+    loaded.unwrap_or_else(|err| {
+        WARNED.call_once(|| {
+            warn!("{err:#}; using the default config instead, see `mynd config check`")
+        });
+        MyndConfig::default()
+    })
+}
+
+/// Where the config is read from and written to.
+pub fn config_path() -> anyhow::Result<PathBuf> {
+    if let Some(path) = CONFIG_PATH.get() {
+        return Ok(path.clone());
+    }
+    confy::get_configuration_file_path(APP_NAME, None).context("failed to find the config file")
+}
+
+/// Load the config from a file at `path`, writing the defaults there if there isn't one.
+pub fn load_config_from(path: &Path) -> anyhow::Result<MyndConfig> {
+    confy::load_path::<MyndConfig>(path)
+        .with_context(|| format!("failed to load cli configs from {}", path.display()))
+}
+
+impl MyndConfig {
+    /// The configured keywords, falling back to the default when none are.
+    pub fn keywords(&self) -> Vec<&str> {
+        if self.keywords.is_empty() {
+            return vec!["todo"];
+        }
+        self.keywords.iter().map(|k| k.as_str()).collect()
+    }
+}
+
 pub fn store_config(cfg: MyndConfig) -> anyhow::Result<()> {
+    if let Some(path) = CONFIG_PATH.get() {
+        return confy::store_path(path, cfg)
+            .with_context(|| format!("failed to store cli configs to {}", path.display()));
+    }
     confy::store(APP_NAME, None, cfg).context("failed to store cli configs")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{load_config_from, or_default, DefaultSort, SaveFileFormat};
+    use crate::testing::Logs;
+
+    #[test]
+    fn corrupt_config_is_an_error_or_a_warning() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corrupt.ron");
+        std::fs::write(&path, "(save_file_format: Yaml)").unwrap();
+
+        let err = load_config_from(&path).unwrap_err();
+        assert!(format!("{err:#}").contains("corrupt.ron"));
+
+        let logs = Logs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let cfg =
+            tracing::subscriber::with_default(subscriber, || or_default(load_config_from(&path)));
+
+        assert!(matches!(cfg.save_file_format, SaveFileFormat::Binary));
+        let logs = logs.contents();
+        assert!(logs.contains("WARN"), "{logs}");
+        assert!(logs.contains("using the default config instead"), "{logs}");
+    }
+
+    #[test]
+    fn loads_config_from_a_given_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profile.ron");
+        std::fs::write(
+            &path,
+            "(save_file_format: Json, default_sort: created, keywords: [\"fixme\"])",
+        )
+        .unwrap();
+
+        let cfg = load_config_from(&path).unwrap();
+
+        assert!(matches!(cfg.save_file_format, SaveFileFormat::Json));
+        assert!(matches!(cfg.default_sort, DefaultSort::Created));
+        assert_eq!(cfg.keywords(), vec!["fixme"]);
+        assert!(cfg.done_toggles, "unset values are the defaults");
+    }
+}
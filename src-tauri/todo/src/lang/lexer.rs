@@ -1,8 +1,12 @@
 use std::fmt::Display;
 
+use serde::Serialize;
+
+use crate::config::BlockDelimiters;
+
 use super::{CharacterTest, Position, Span};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub enum TokenKind {
     TodoKeyword,
     String,
@@ -21,7 +25,7 @@ impl Display for TokenKind {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub struct Token<'src> {
     pub kind: TokenKind,
     pub text: &'src str,
@@ -34,21 +38,49 @@ impl<'src> Token<'src> {
     }
 }
 
+/// Keywords that start a todo, unless others are configured.
+pub const DEFAULT_KEYWORDS: &[&str] = &["todo"];
+
 pub struct Lexer<'src> {
     position: Position,
     eof_pos: Position,
     src: &'src [u8],
+    tab_width: u32,
+    keywords: Vec<String>,
+    delimiters: BlockDelimiters,
 }
 
 impl<'src> Lexer<'src> {
     pub fn new(src: &'src str) -> Self {
+        Self::with_keywords(src, DEFAULT_KEYWORDS)
+    }
+
+    /// Lex any of `keywords` as a [`TokenKind::TodoKeyword`], instead of just "todo".
+    pub fn with_keywords(src: &'src str, keywords: &[&str]) -> Self {
         Lexer {
             position: Position::default(),
             eof_pos: Position::default(),
             src: src.as_bytes(),
+            tab_width: 1,
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            delimiters: BlockDelimiters::default(),
         }
     }
 
+    /// Which delimiters lex as a [`TokenKind::MultilineString`]; braces only by default.
+    pub fn with_block_delimiters(mut self, delimiters: BlockDelimiters) -> Self {
+        self.delimiters = delimiters;
+        self
+    }
+
+    /// How many columns a tab advances `col` by. Defaults to 1, so that by default `col` counts
+    /// bytes on the line, as LSP clients expect of `character` offsets for ascii text.
+    /// This doesn't affect the byte position, `value`.
+    pub fn with_tab_width(mut self, tab_width: u32) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
     fn input_slice(&self, range: (u32, u32)) -> &'src str {
         let (start, end) = (range.0 as usize, range.1 as usize);
 
@@ -76,11 +108,13 @@ impl<'src> Lexer<'src> {
     }
 
     fn check_and_bump_new_line(&mut self) {
-        if let Some(b'\n') = self.ch() {
-            self.position.line += 1;
-            self.position.col = 0;
-        } else {
-            self.position.col += 1;
+        match self.ch() {
+            Some(b'\n') => {
+                self.position.line += 1;
+                self.position.col = 0;
+            }
+            Some(b'\t') => self.position.col += self.tab_width,
+            _ => self.position.col += 1,
         };
     }
 
@@ -122,7 +156,13 @@ impl<'src> Lexer<'src> {
         };
 
         let token = match ch {
-            b'{' => self.multiline_string(),
+            b'{' if self.delimiters.braces => self.multiline_string(),
+            b'<' if self.delimiters.heredoc
+                && self.peek_char() == Some(&b'<')
+                && self.peek_n_char(1).passes(|c| c.is_ascii_alphabetic()) =>
+            {
+                self.heredoc_string()
+            }
             c if c.is_ascii_alphabetic() => self.keyword_or_identifier(),
             _ => self.string(None),
         };
@@ -139,10 +179,11 @@ impl<'src> Lexer<'src> {
 
         use TokenKind::*;
 
-        match string {
-            "todo" => Token::new(TodoKeyword, string, location.spanning_to(self.position)),
-            _ => self.string(Some(location)),
+        if self.keywords.iter().any(|k| k == string) {
+            return Token::new(TodoKeyword, string, location.spanning_to(self.position));
         }
+
+        self.string(Some(location))
     }
 
     fn string(&mut self, start: Option<Position>) -> Token<'src> {
@@ -176,6 +217,68 @@ impl<'src> Lexer<'src> {
             start_pos.spanning_to(self.position),
         )
     }
+
+    /// `<<END`, then the lines up to one that's just `END`, give or take whitespace. The text
+    /// is from right after the opening `END` up to the closing one, like between braces.
+    fn heredoc_string(&mut self) -> Token<'src> {
+        let start_pos = self.position;
+
+        self.step(); // eat the '<'
+        self.step(); // and the other
+
+        let (s, e) = self.read_while(|&c| c.is_ascii_alphanumeric() || c == b'_');
+        let tag = self.input_slice((s, e)).as_bytes();
+
+        let mut line_start = self.src[e as usize..]
+            .iter()
+            .position(|&c| c == b'\n')
+            .map(|i| e as usize + i + 1);
+        let mut closing = None;
+        while let Some(start) = line_start {
+            let end = self.src[start..]
+                .iter()
+                .position(|&c| c == b'\n')
+                .map_or(self.src.len(), |i| start + i);
+            let line = &self.src[start..end];
+            let indent = line.iter().take_while(|c| c.is_ascii_whitespace()).count();
+            if line.trim_ascii() == tag {
+                closing = Some(start + indent);
+                break;
+            }
+            line_start = (end < self.src.len()).then_some(end + 1);
+        }
+
+        // a block that isn't closed runs to the end, as with braces
+        let (text_end, last) = match closing {
+            Some(at) => (at, at + tag.len() - 1),
+            None => (self.src.len(), self.src.len().saturating_sub(1)),
+        };
+        while (self.position.value as usize) < last {
+            self.step();
+        }
+
+        let string = self.input_slice((e, text_end as u32));
+
+        Token::new(
+            TokenKind::MultilineString,
+            string,
+            start_pos.spanning_to(self.position),
+        )
+    }
+}
+
+/// The tokens up to, and not including, the end of the file.
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Token<'src>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.next_token();
+        if token.kind == TokenKind::Eof {
+            return None;
+        }
+
+        Some(token)
+    }
 }
 
 #[cfg(test)]
@@ -344,17 +447,113 @@ todo {
         );
     }
 
-    impl<'src> Iterator for lexer::Lexer<'src> {
-        type Item = Token<'src>;
+    #[test]
+    fn counts_tabs_by_tab_width() {
+        let src = "\ttodo {\n\t\tone\n\t}";
+
+        let spans = |tab_width| {
+            lexer::Lexer::new(src)
+                .with_tab_width(tab_width)
+                .map(|t| {
+                    let (start, end) = (t.span.start, t.span.end);
+                    (
+                        (start.value, start.line, start.col),
+                        (end.value, end.line, end.col),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
 
-        fn next(&mut self) -> Option<Self::Item> {
-            let token = self.next_token();
-            if token.kind == TokenKind::Eof {
-                return None;
-            }
+        assert_eq!(
+            spans(1),
+            vec![((1, 0, 1), (4, 0, 4)), ((6, 0, 6), (15, 2, 1))]
+        );
+        assert_eq!(
+            spans(4),
+            vec![((1, 0, 4), (4, 0, 7)), ((6, 0, 9), (15, 2, 4))]
+        );
+    }
 
-            return Some(token);
-        }
+    #[test]
+    fn lexes_heredoc_blocks_only_when_enabled() {
+        let src = "todo <<END\nfn main() {\n    println!(\"{}\", 1);\n}\n  END\ntodo after";
+
+        let heredoc = BlockDelimiters {
+            braces: true,
+            heredoc: true,
+        };
+        let tokens: Vec<_> = Lexer::new(src)
+            .with_block_delimiters(heredoc)
+            .map(|t| (t.kind, t.text, (t.span.end.line, t.span.end.col)))
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::TodoKeyword, "todo", (0, 3)),
+                (
+                    TokenKind::MultilineString,
+                    "\nfn main() {\n    println!(\"{}\", 1);\n}\n  ",
+                    (4, 4)
+                ),
+                (TokenKind::TodoKeyword, "todo", (5, 3)),
+                (TokenKind::String, "after", (5, 9)),
+            ]
+        );
+
+        let unclosed: Vec<_> = Lexer::new("todo <<END\n{ one }\n")
+            .with_block_delimiters(heredoc)
+            .map(|t| (t.kind, t.text))
+            .collect();
+        assert_eq!(
+            unclosed,
+            vec![
+                (TokenKind::TodoKeyword, "todo"),
+                (TokenKind::MultilineString, "\n{ one }\n"),
+            ]
+        );
+
+        let braces_only: Vec<_> = Lexer::new("todo <<END").map(|t| (t.kind, t.text)).collect();
+        assert_eq!(
+            braces_only,
+            vec![
+                (TokenKind::TodoKeyword, "todo"),
+                (TokenKind::String, "<<END"),
+            ]
+        );
+
+        let no_braces: Vec<_> = Lexer::new("todo { one }")
+            .with_block_delimiters(BlockDelimiters {
+                braces: false,
+                heredoc: true,
+            })
+            .map(|t| (t.kind, t.text))
+            .collect();
+        assert_eq!(
+            no_braces,
+            vec![
+                (TokenKind::TodoKeyword, "todo"),
+                (TokenKind::String, "{ one }"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lexes_custom_keywords() {
+        let src = "task this one\ntodo not this one";
+
+        let tokens: Vec<_> = lexer::Lexer::with_keywords(src, &["task", "fixme"])
+            .map(|t| (t.kind, t.text))
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::TodoKeyword, "task"),
+                (TokenKind::String, "this one"),
+                (TokenKind::String, "todo not this one"),
+            ]
+        );
     }
 
     #[test]
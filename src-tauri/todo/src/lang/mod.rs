@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[derive(Debug, PartialEq, Clone, Copy, Default, serde::Serialize)]
 pub struct Position {
     /// Byte position. Zero-based.
     pub value: u32,
@@ -6,7 +6,7 @@ pub struct Position {
     pub col: u32,
 }
 
-#[derive(Debug, PartialEq, Default, Clone, Copy)]
+#[derive(Debug, PartialEq, Default, Clone, Copy, serde::Serialize)]
 pub struct Span {
     pub start: Position,
     pub end: Position,
@@ -33,6 +33,16 @@ impl CharacterTest for Option<&u8> {
 
 pub mod lexer;
 
+/// Parse `src` the way the config says to, with its keywords, block delimiters and indentation.
+pub fn parse(src: &str, cfg: &crate::config::MyndConfig) -> parser::ast::Text {
+    let lexer = lexer::Lexer::with_keywords(src, &cfg.keywords())
+        .with_block_delimiters(cfg.block_delimiters)
+        .with_tab_width(cfg.tab_width);
+    parser::Parser::new(lexer)
+        .preserving_indent(cfg.preserve_indent)
+        .parse()
+}
+
 pub mod parser {
 
     use super::{
@@ -43,10 +53,30 @@ pub mod parser {
     pub mod ast {
         #[derive(Debug)]
         pub struct TodoItem {
+            /// From a `[x]` or `[ ]` before the message; `None` without either.
+            pub done: Option<bool>,
             pub message: String,
             pub span: super::Span,
         }
 
+        impl TodoItem {
+            /// Words read per minute, for [`TodoItem::reading_minutes`].
+            const WORDS_PER_MINUTE: usize = 200;
+
+            pub fn line_count(&self) -> usize {
+                self.message.lines().count()
+            }
+
+            pub fn word_count(&self) -> usize {
+                self.message.split_whitespace().count()
+            }
+
+            /// Rough time to read the message, rounded up to the minute.
+            pub fn reading_minutes(&self) -> usize {
+                self.word_count().div_ceil(Self::WORDS_PER_MINUTE)
+            }
+        }
+
         #[derive(Debug)]
         pub enum Item {
             OneLine(TodoItem),
@@ -65,9 +95,19 @@ pub mod parser {
         }
     }
 
+    impl ast::Text {
+        /// Parse with any of `keywords` starting a todo.
+        pub fn with_keywords(src: &str, keywords: &[&str]) -> Self {
+            Parser::new(lexer::Lexer::with_keywords(src, keywords)).parse()
+        }
+    }
+
     pub struct Parser<'src> {
         lexer: lexer::Lexer<'src>,
         peeked: Option<Token<'src>>,
+        /// Keep the indentation of lines in text blocks relative to the least indented one,
+        /// instead of trimming it all.
+        preserve_indent: bool,
     }
 
     impl<'src> Parser<'src> {
@@ -75,9 +115,15 @@ pub mod parser {
             Self {
                 lexer,
                 peeked: None,
+                preserve_indent: false,
             }
         }
 
+        pub fn preserving_indent(mut self, preserve: bool) -> Self {
+            self.preserve_indent = preserve;
+            self
+        }
+
         fn next_token(&mut self) -> Token<'src> {
             return match self.peeked.take() {
                 Some(t) => t,
@@ -122,10 +168,33 @@ pub mod parser {
                     found: TokenKind::TodoKeyword,
                     span: token.span,
                 }),
-                TokenKind::String => Ok(ast::Item::OneLine(ast::TodoItem {
-                    message: token.text.to_string(),
-                    span: token.span,
-                })),
+                TokenKind::String => {
+                    let (done, message) = done_marker(token.text);
+                    Ok(ast::Item::OneLine(ast::TodoItem {
+                        done,
+                        message: message.to_string(),
+                        span: token.span,
+                    }))
+                }
+                TokenKind::MultilineString if self.preserve_indent => {
+                    let lines = token.text.lines().filter(|line| !line.trim().is_empty());
+                    let base_indent = lines
+                        .clone()
+                        .map(|line| line.len() - line.trim_start().len())
+                        .min()
+                        .unwrap_or_default();
+
+                    let message = lines
+                        .map(|line| line[base_indent..].trim_end())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+
+                    Ok(ast::Item::Multiline(ast::TodoItem {
+                        done: None,
+                        message,
+                        span: token.span,
+                    }))
+                }
                 TokenKind::MultilineString => {
                     let message = token
                         .text
@@ -138,6 +207,7 @@ pub mod parser {
                         .join("\n");
 
                     Ok(ast::Item::Multiline(ast::TodoItem {
+                        done: None,
                         message,
                         span: token.span,
                     }))
@@ -147,6 +217,23 @@ pub mod parser {
         }
     }
 
+    /// Split a leading `[x]` (done) or `[ ]` (pending) off a one line todo's text.
+    fn done_marker(text: &str) -> (Option<bool>, &str) {
+        let (done, rest) = if let Some(rest) = text.strip_prefix("[ ]") {
+            (false, rest)
+        } else if let Some(rest) = text.strip_prefix("[x]").or(text.strip_prefix("[X]")) {
+            (true, rest)
+        } else {
+            return (None, text);
+        };
+
+        // a marker is only one when there's a message after it
+        match rest.strip_prefix(char::is_whitespace).map(str::trim_start) {
+            Some(message) if !message.is_empty() => (Some(done), message),
+            _ => (None, text),
+        }
+    }
+
     pub type Result<T> = std::result::Result<T, ParseError>;
 
     #[derive(thiserror::Error, Debug)]
@@ -163,11 +250,21 @@ pub mod parser {
         },
     }
 
+    impl ParseError {
+        pub fn span(&self) -> &Span {
+            match self {
+                ParseError::ExtraText(s) => s,
+                ParseError::UnexpectedEof(s) => s,
+                ParseError::UnexpectedToken { span, .. } => span,
+            }
+        }
+    }
+
     #[cfg(test)]
     mod tests {
         use insta::assert_debug_snapshot;
 
-        use crate::lang::lexer;
+        use crate::{config::BlockDelimiters, lang::lexer};
 
         use super::ast;
 
@@ -286,6 +383,7 @@ pub mod parser {
                     Ok(
                         OneLine(
                             TodoItem {
+                                done: None,
                                 message: "run this test",
                                 span: Span {
                                     start: Position {
@@ -305,6 +403,7 @@ pub mod parser {
                     Ok(
                         OneLine(
                             TodoItem {
+                                done: None,
                                 message: "run this as well",
                                 span: Span {
                                     start: Position {
@@ -324,6 +423,7 @@ pub mod parser {
                     Ok(
                         OneLine(
                             TodoItem {
+                                done: None,
                                 message: "and this",
                                 span: Span {
                                     start: Position {
@@ -363,6 +463,7 @@ pub mod parser {
                     Ok(
                         OneLine(
                             TodoItem {
+                                done: None,
                                 message: "run this test",
                                 span: Span {
                                     start: Position {
@@ -382,6 +483,7 @@ pub mod parser {
                     Ok(
                         Multiline(
                             TodoItem {
+                                done: None,
                                 message: "run this test with a single line toodo\nas well as this multiline todo\nblah blah",
                                 span: Span {
                                     start: Position {
@@ -403,6 +505,79 @@ pub mod parser {
             "###);
         }
 
+        #[test]
+        fn parses_heredoc_blocks_with_braces_in_them() {
+            let src = "todo <<EOF\n    if (x) { y(); }\n    and }{ these\nEOF\ntodo next";
+            let lexer = lexer::Lexer::new(src).with_block_delimiters(BlockDelimiters {
+                braces: true,
+                heredoc: true,
+            });
+
+            let text = super::Parser::new(lexer).parse();
+            let messages: Vec<_> = text
+                .items
+                .iter()
+                .map(|item| match item {
+                    Ok(ast::Item::Multiline(t)) => ("multiline", t.message.as_str()),
+                    Ok(ast::Item::OneLine(t)) => ("one line", t.message.as_str()),
+                    Err(err) => panic!("unexpected error: {err}"),
+                })
+                .collect();
+
+            assert_eq!(
+                messages,
+                vec![
+                    ("multiline", "if (x) { y(); }\nand }{ these"),
+                    ("one line", "next")
+                ]
+            );
+        }
+
+        #[test]
+        fn parses_done_markers() {
+            let src = "todo [x] fix bug\ntodo [ ] write docs\ntodo plain one\ntodo [x]\ntodo [x]y";
+            let text = ast::Text::from(src);
+            let items: Vec<_> = text
+                .items
+                .iter()
+                .map(|item| match item {
+                    Ok(ast::Item::OneLine(t)) => (t.done, t.message.as_str()),
+                    other => panic!("expected a one line todo, got {other:?}"),
+                })
+                .collect();
+
+            assert_eq!(
+                items,
+                vec![
+                    (Some(true), "fix bug"),
+                    (Some(false), "write docs"),
+                    (None, "plain one"),
+                    (None, "[x]"),
+                    (None, "[x]y"),
+                ]
+            );
+        }
+
+        #[test]
+        fn counts_multiline_size() {
+            let src = r#"todo {
+        run this test with a single line toodo
+        as well as this multiline todo
+
+        blah blah
+    }"#;
+
+            let text = ast::Text::from(src);
+
+            let Some(Ok(ast::Item::Multiline(item))) = text.items.first() else {
+                panic!("expected a multiline todo, got: {:?}", text.items);
+            };
+
+            assert_eq!(item.line_count(), 3);
+            assert_eq!(item.word_count(), 16);
+            assert_eq!(item.reading_minutes(), 1);
+        }
+
         #[test]
         fn parses_todos_errors() {
             let src = r#"run this test
@@ -7,17 +7,25 @@ use todo::persist::{ActualTodosDB, TodosDatabase};
 use todo::{Todo, TodoID, Todos};
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
-use tower_lsp::{Client, LanguageServer, LspService, Server};
+use tower_lsp::{Client, ClientSocket, LanguageServer, LspService, Server};
 
-use crate::lang;
-use crate::lang::parser::ast;
+use todo::config;
+use todo::lang;
+use todo::lang::parser::ast;
+
+/// Custom request for every todo in the store, e.g. for an editor's todo panel.
+const LIST_TODOS_METHOD: &str = "mynd/listTodos";
 
 #[derive(Debug)]
-struct Backend {
+struct Backend<DB: TodosDatabase = ActualTodosDB> {
     client: Client,
-    todos: Todos<ActualTodosDB>,
+    todos: Todos<DB>,
     /// To remember the set of todos in a buffer
     seen_todo_ids_per_buffer: DashMap<Url, HashSet<TodoID>>,
+    /// How buffers are parsed: keywords, block delimiters and indentation.
+    cfg: config::MyndConfig,
+    /// Whether saving a buffer saves the todos; not when started by the editor `edit` opens.
+    saves: bool,
 }
 
 struct ChangedDocumentItem {
@@ -26,35 +34,34 @@ struct ChangedDocumentItem {
     pub text: String,
 }
 
-impl lang::Position {
-    fn into_lsp_pos(self) -> Position {
-        Position {
-            line: self.line,
-            character: self.col,
-        }
+fn lsp_pos(pos: lang::Position) -> Position {
+    Position {
+        line: pos.line,
+        character: pos.col,
     }
 }
 
-impl lang::Span {
-    fn into_lsp_range(self) -> Range {
-        Range {
-            start: self.start.into_lsp_pos(),
-            end: self.end.into_lsp_pos(),
-        }
+fn lsp_range(span: lang::Span) -> Range {
+    Range {
+        start: lsp_pos(span.start),
+        end: lsp_pos(span.end),
     }
 }
 
-impl lang::parser::ParseError {
-    fn span(&self) -> &lang::Span {
-        match self {
-            lang::parser::ParseError::ExtraText(s) => s,
-            lang::parser::ParseError::UnexpectedEof(s) => s,
-            lang::parser::ParseError::UnexpectedToken { span, .. } => span,
-        }
-    }
+/// Every parse error in `text`, as the full set of diagnostics to publish for its buffer.
+fn diagnostics(text: &ast::Text) -> Vec<Diagnostic> {
+    text.items
+        .iter()
+        .filter_map(|item| item.as_ref().err())
+        .map(|err| Diagnostic::new_simple(lsp_range(*err.span()), err.to_string()))
+        .collect()
 }
 
-impl Backend {
+impl<DB: TodosDatabase> Backend<DB> {
+    fn parse(&self, text: &str) -> ast::Text {
+        lang::parse(text, &self.cfg)
+    }
+
     async fn log_error(&self, err: anyhow::Error) {
         self.client
             .log_message(MessageType::ERROR, format!("{err:#}"))
@@ -62,79 +69,50 @@ impl Backend {
     }
 
     async fn on_change(&self, params: ChangedDocumentItem) {
-        // Back up everything in the store here
-        let current_store: HashMap<TodoID, _> = self
-            .todos
-            .db
-            .get_all_todos()
-            .unwrap()
-            .into_iter()
-            .chain(self.todos.get_all().unwrap())
-            .map(|t| (t.id.clone(), t))
-            .collect();
+        let text = self.parse(&params.text);
 
-        let text = ast::Text::from(params.text.as_str());
+        // Start over from this buffer alone, so nothing from a previous parse lingers.
+        let diagnostics = diagnostics(&text);
+
+        self.client
+            .publish_diagnostics(params.uri.clone(), diagnostics, params.version)
+            .await;
+
+        let applied = match self.todos.apply_todolang_text(text) {
+            Ok(applied) => applied,
+            Err(err) => {
+                // without knowing what's in the buffer, nothing in it can be called dangling
+                return self.log_error(err).await;
+            }
+        };
+
+        for err in &applied.errors {
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("[Diagnostic] {err}: {:?}", err.span()),
+                )
+                .await;
+        }
 
         let mut dangling_todos_to_delete = self
             .seen_todo_ids_per_buffer
             .entry(params.uri.clone())
             .or_default();
-
-        let mut diagnostics = vec![];
         let mut new_previous = HashSet::new();
 
-        for maybeitem in text.items {
-            match maybeitem {
-                Ok(item) => {
-                    let todo = match item {
-                        ast::Item::OneLine(t) => t,
-                        ast::Item::Multiline(t) => t,
-                    };
-                    let id = TodoID::hash_message(&todo.message);
-                    let todo = current_store
-                        .get(&id)
-                        .cloned()
-                        .unwrap_or_else(|| Todo::new(todo.message));
-
-                    // Remove from the persistent store before add (thus updating)
-                    if let Err(err) = self.todos.remove(&id.0) {
-                        self.log_error(err).await;
-                    };
-
-                    match self.todos.add(todo) {
-                        Ok(_) => {
-                            self.client
-                                .log_message(
-                                    MessageType::INFO,
-                                    format!("added todo message, id: {:?}", id),
-                                )
-                                .await;
-                        }
-                        Err(error) => self.log_error(error).await,
-                    };
-
-                    dangling_todos_to_delete.remove(&id);
-                    new_previous.insert(id);
-                }
-                Err(err) => {
-                    diagnostics.push(Diagnostic::new_simple(
-                        err.span().into_lsp_range(),
-                        err.to_string(),
-                    ));
-                    self.client
-                        .log_message(
-                            MessageType::WARNING,
-                            format!("[Diagnostic] {err}: {:?}", err.span()),
-                        )
-                        .await;
-                }
-            }
+        for id in applied.ids {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("added todo message, id: {:?}", id),
+                )
+                .await;
+
+            dangling_todos_to_delete.remove(&id);
+            new_previous.insert(id);
         }
 
-        self.client
-            .publish_diagnostics(params.uri.clone(), diagnostics, params.version)
-            .await;
-
         for todoid in dangling_todos_to_delete.iter() {
             if let Err(err) = self.todos.remove(&todoid.0) {
                 self.log_error(err).await
@@ -147,6 +125,16 @@ impl Backend {
             .insert(params.uri.clone(), new_previous);
     }
 
+    async fn list_todos(&self) -> Result<Vec<Todo>> {
+        self.todos
+            .get_all()
+            .map_err(|err| tower_lsp::jsonrpc::Error {
+                code: tower_lsp::jsonrpc::ErrorCode::InternalError,
+                message: format!("{err:#}").into(),
+                data: None,
+            })
+    }
+
     async fn read_text_by_uri(&self, uri: Url) -> Option<String> {
         return match std::fs::read_to_string(uri.path()).context("failed to read file after save") {
             Ok(text) => Some(text),
@@ -161,7 +149,7 @@ impl Backend {
 }
 
 #[tower_lsp::async_trait]
-impl LanguageServer for Backend {
+impl<DB: TodosDatabase + Send + Sync + 'static> LanguageServer for Backend<DB> {
     async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
@@ -224,6 +212,10 @@ impl LanguageServer for Backend {
         })
         .await;
 
+        if !self.saves {
+            return;
+        }
+
         if let Err(err) = self.todos.flush() {
             self.client
                 .log_message(MessageType::ERROR, format!("{err:#}"))
@@ -241,7 +233,12 @@ impl LanguageServer for Backend {
         self.on_change(ChangedDocumentItem {
             uri: params.text_document.uri,
             version: Some(params.text_document.version),
-            text: params.content_changes[0].text.clone(),
+            // the whole buffer with full sync; the last change is the latest one regardless
+            text: params
+                .content_changes
+                .last()
+                .map(|change| change.text.clone())
+                .unwrap_or_default(),
         })
         .await;
     }
@@ -256,7 +253,7 @@ impl LanguageServer for Backend {
             return Ok(None);
         };
 
-        let text = ast::Text::from(text.as_ref());
+        let text = self.parse(text.as_ref());
         let todos = match self.todos.get_all() {
             Ok(list) => list,
             Err(err) => {
@@ -277,21 +274,31 @@ impl LanguageServer for Backend {
             .into_iter()
             .flatten()
             .map(|item| match item {
-                ast::Item::OneLine(t) => t,
-                ast::Item::Multiline(t) => t,
+                ast::Item::OneLine(t) => (t, false),
+                ast::Item::Multiline(t) => (t, true),
             })
-            .filter_map(|item| {
+            .filter_map(|(item, multiline)| {
                 let todoid = todo::TodoID::hash_message(&item.message);
                 if let Some(todo) = todos.get(&todoid) {
                     let is_done = if todo.done { "[x]" } else { "[ ]" };
                     let creation_time =
                         format!("created on: {}", todo.created_at.to_local_date_string());
 
+                    let mut title = format!("{}, {}", is_done, creation_time);
+                    if multiline {
+                        title.push_str(&format!(
+                            ", {} lines, {} words, ~{} min read",
+                            item.line_count(),
+                            item.word_count(),
+                            item.reading_minutes()
+                        ));
+                    }
+
                     return Some(CodeLens {
-                        range: item.span.into_lsp_range(),
+                        range: lsp_range(item.span),
                         data: None,
                         command: Some(Command {
-                            title: format!("{}, {}", is_done, creation_time),
+                            title,
                             command: "mark_done".to_string(), // TODO: implement this...
                             arguments: Some(vec![Value::String(todo.id.0.to_string())]),
                         }),
@@ -345,10 +352,84 @@ async fn run() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(|client| Backend {
+    let cfg = config::MyndConfig {
+        // LSP positions count characters, not columns
+        tab_width: 1,
+        ..config::load_config_or_default()
+    };
+
+    let (service, socket) = service(|client| Backend {
         client,
         todos: Todos::load_up_with_persistor(),
         seen_todo_ids_per_buffer: DashMap::new(),
+        cfg,
+        saves: std::env::var_os(crate::edit::EDIT_SESSION_VAR).is_none(),
     });
     Server::new(stdin, stdout, socket).serve(service).await;
 }
+
+/// The server, with the custom methods on top of the standard ones.
+fn service<DB: TodosDatabase + Send + Sync + 'static>(
+    backend: impl FnOnce(Client) -> Backend<DB>,
+) -> (LspService<Backend<DB>>, ClientSocket) {
+    LspService::build(backend)
+        .custom_method(LIST_TODOS_METHOD, Backend::list_todos)
+        .finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use todo::testing::MemoryDB;
+    use tower::{Service, ServiceExt};
+    use tower_lsp::jsonrpc::Request;
+
+    use super::*;
+
+    #[test]
+    fn lists_todos_on_request() {
+        let stored = vec![Todo::new("1".to_string()), Todo::new("2".to_string())];
+
+        let (mut service, _socket) = service(|client| Backend {
+            client,
+            todos: Todos::new(MemoryDB::with(stored.clone())),
+            seen_todo_ids_per_buffer: DashMap::new(),
+            cfg: Default::default(),
+            saves: true,
+        });
+        service.inner().todos.reload().unwrap();
+
+        let requests = [
+            Request::build("initialize")
+                .params(json!({ "capabilities": {} }))
+                .id(1)
+                .finish(),
+            Request::build(LIST_TODOS_METHOD).id(2).finish(),
+        ];
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let response = runtime.block_on(async {
+            let mut response = None;
+            for request in requests {
+                response = service.ready().await.unwrap().call(request).await.unwrap();
+            }
+            response.expect("a response to the request")
+        });
+
+        let (_, result) = response.into_parts();
+        let listed: Vec<Todo> = serde_json::from_value(result.unwrap()).unwrap();
+        let ids = |todos: &[Todo]| todos.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&listed), ids(&stored));
+    }
+
+    #[test]
+    fn diagnostics_are_recomputed_from_each_buffer() {
+        let erroring = ast::Text::from("run this test\ntodo todo\ntodo");
+        assert!(!diagnostics(&erroring).is_empty());
+
+        let clean = ast::Text::from("todo run this test\ntodo and this");
+        assert_eq!(diagnostics(&clean), vec![]);
+    }
+}
@@ -1,27 +1,52 @@
 use std::{
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Display,
-    sync::{Mutex, MutexGuard},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard, TryLockError,
+    },
+    time::{Duration, Instant},
     usize,
 };
 
-use anyhow::anyhow;
-use chrono::{Local, TimeZone};
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
 use collection::array::TodoArrayList;
 use collection::TodoCollection;
 use persist::{ActualTodosDB, TodosDatabase};
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
+use tracing::{debug, trace, warn};
 
 mod collection;
-mod config;
-mod lang;
+pub mod config;
+pub mod lang;
 pub mod persist;
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Clone, Hash)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Clone, Hash, JsonSchema)]
 pub struct TodoID(pub Box<str>);
 impl TodoID {
+    /// How many characters of the id are shown, and typically enough to find a todo by.
+    pub const SHORT_LEN: usize = 8;
+
     pub fn hash_message(message: &str) -> TodoID {
         TodoID(sha256::digest(message).into())
     }
+
+    /// An id that's distinct for each creation time, for when identical messages are allowed.
+    pub fn hash_message_at(message: &str, created_at: &TodoTime) -> TodoID {
+        let nanos = created_at.0.timestamp_nanos_opt().unwrap_or_default();
+        TodoID(sha256::digest(format!("{nanos}:{message}")).into())
+    }
+
+    /// The first few characters of the id, which can be used in place of the whole id as long
+    /// as no other todo's id starts with them.
+    pub fn short(&self) -> &str {
+        match self.0.char_indices().nth(Self::SHORT_LEN) {
+            Some((end, _)) => &self.0[..end],
+            None => &self.0,
+        }
+    }
 }
 
 impl From<String> for TodoID {
@@ -36,7 +61,7 @@ impl From<&str> for TodoID {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, PartialOrd, Clone)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, JsonSchema)]
 pub struct TodoTime(chrono::DateTime<chrono::Utc>);
 
 impl TodoTime {
@@ -46,6 +71,63 @@ impl TodoTime {
             .format("%m/%d/%Y %H:%M")
             .to_string()
     }
+
+    /// Format in UTC with a `strftime` style format string.
+    pub fn format_utc(&self, fmt: &str) -> String {
+        self.0.format(fmt).to_string()
+    }
+
+    /// Format in the local timezone with a `strftime` style format string.
+    pub fn format_local(&self, fmt: &str) -> String {
+        Local
+            .from_utc_datetime(&self.0.naive_utc())
+            .format(fmt)
+            .to_string()
+    }
+
+    /// How long ago it was, like "3 hours ago", "yesterday" or "just now"; or for times ahead,
+    /// "in 2 days".
+    pub fn to_relative_string(&self) -> String {
+        self.relative_to(&Self::now())
+    }
+
+    fn relative_to(&self, now: &TodoTime) -> String {
+        let delta = now.0.signed_duration_since(self.0);
+        let ahead = delta < chrono::Duration::zero();
+        let delta = delta.abs();
+
+        let plural = |count: i64, unit: &str| match count {
+            1 => format!("1 {unit}"),
+            _ => format!("{count} {unit}s"),
+        };
+
+        let amount = if delta < chrono::Duration::minutes(1) {
+            return "just now".to_string();
+        } else if delta < chrono::Duration::hours(1) {
+            plural(delta.num_minutes(), "minute")
+        } else if delta < chrono::Duration::days(1) {
+            plural(delta.num_hours(), "hour")
+        } else if delta < chrono::Duration::days(2) {
+            return if ahead { "tomorrow" } else { "yesterday" }.to_string();
+        } else if delta < chrono::Duration::days(30) {
+            plural(delta.num_days(), "day")
+        } else if delta < chrono::Duration::days(365) {
+            plural(delta.num_days() / 30, "month")
+        } else {
+            plural(delta.num_days() / 365, "year")
+        };
+
+        if ahead {
+            format!("in {amount}")
+        } else {
+            format!("{amount} ago")
+        }
+    }
+
+    /// The day it was in the given timezone, e.g. [`Local`].
+    pub fn date_in<Tz: TimeZone>(&self, tz: &Tz) -> NaiveDate {
+        self.0.with_timezone(tz).date_naive()
+    }
 }
 
 impl Display for TodoTime {
@@ -64,24 +146,287 @@ impl TodoTime {
     pub fn now() -> Self {
         Self(chrono::Utc::now())
     }
+
+    /// Stands in for times that weren't recorded; earlier than any real one.
+    fn unknown() -> Self {
+        Self(DateTime::from_timestamp_nanos(0))
+    }
+
+    /// Local midnight at the start of `date`.
+    pub fn start_of_local_day(date: NaiveDate) -> anyhow::Result<Self> {
+        let midnight = Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+            .earliest()
+            .context("local midnight doesn't exist on that date")?;
+        Ok(Self(midnight.with_timezone(&Utc)))
+    }
+
+    /// The last moment of `date` in local time, just before the next day's midnight.
+    pub fn end_of_local_day(date: NaiveDate) -> anyhow::Result<Self> {
+        let next_day = date.succ_opt().context("there's no day after that date")?;
+        let next_midnight = Self::start_of_local_day(next_day)?;
+        Ok(Self(next_midnight.0 - chrono::Duration::nanoseconds(1)))
+    }
+}
+
+impl std::str::FromStr for TodoTime {
+    type Err = anyhow::Error;
+
+    /// Accepts an RFC 3339 timestamp, a date (local midnight), or a duration (e.g. `7d`) to go
+    /// back from now.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+            return Ok(Self(time.with_timezone(&Utc)));
+        }
+
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Self::start_of_local_day(date);
+        }
+
+        let ago = humantime::parse_duration(s)
+            .map_err(|_| anyhow!("expected an RFC 3339 time, a date, or a duration like 7d"))?;
+        let ago = chrono::Duration::from_std(ago).context("duration is too large")?;
+
+        Ok(Self(Utc::now() - ago))
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 pub struct Todo {
     pub id: TodoID,
     pub message: String,
     pub created_at: TodoTime,
     pub done: bool,
+    /// Position in the list, top to bottom.
+    #[serde(default)]
+    pub order: u32,
+    /// Pinned todos stay above the rest.
+    #[serde(default)]
+    pub pinned: bool,
+    /// When the todo was last changed, e.g. marked done or moved.
+    /// Todos saved before this existed start off with it at `created_at`.
+    #[serde(default = "TodoTime::unknown")]
+    pub updated_at: TodoTime,
+    /// How urgent the todo is; higher is more urgent.
+    #[serde(default)]
+    pub priority: u8,
 }
 
 impl Todo {
     pub fn new(message: String) -> Self {
+        let created_at = TodoTime::now();
         Self {
             id: TodoID::hash_message(&message),
             message,
-            created_at: Default::default(),
+            updated_at: created_at.clone(),
+            created_at,
             done: false,
+            order: 0,
+            pinned: false,
+            priority: 0,
+        }
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = TodoTime::now();
+    }
+
+    /// Todos from before `updated_at` existed haven't changed since they were created, as far
+    /// as we know.
+    fn backfill_updated_at(&mut self) {
+        if self.updated_at < self.created_at {
+            self.updated_at = self.created_at.clone();
+        }
+    }
+
+    /// Whether the message spans more than one line.
+    pub fn is_multiline(&self) -> bool {
+        self.message.contains('\n')
+    }
+
+    /// Whether this todo was created within the given bounds, inclusively. A missing bound is
+    /// open-ended.
+    pub fn created_between(&self, since: Option<&TodoTime>, until: Option<&TodoTime>) -> bool {
+        since.is_none_or(|since| self.created_at >= *since)
+            && until.is_none_or(|until| self.created_at <= *until)
+    }
+}
+
+/// What [`Todos::reindex`] did.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reindexed {
+    /// How many todos got a new id.
+    pub changed: usize,
+    /// How many todos were dropped for ending up with the same id as an earlier one.
+    pub dropped: usize,
+}
+
+/// What [`Todos::apply_todolang`] did.
+#[derive(Debug, Default)]
+pub struct TodolangApplied {
+    /// The ids of the todos in the text, in the order they're in it.
+    pub ids: Vec<TodoID>,
+    /// What couldn't be read as a todo, and was skipped.
+    pub errors: Vec<lang::parser::ParseError>,
+}
+
+/// How one list of todos became another, going by their ids.
+#[derive(Debug, Default)]
+pub struct ListDiff {
+    /// In the later list only.
+    pub added: Vec<Todo>,
+    /// In the earlier list only.
+    pub removed: Vec<Todo>,
+    /// In both, done in the later list but not in the earlier one.
+    pub completed: Vec<Todo>,
+}
+
+impl ListDiff {
+    /// Compare `before` to `after`, keeping the order each todo is in the list it's taken from.
+    pub fn between(before: &[Todo], after: &[Todo]) -> Self {
+        let earlier: HashMap<&TodoID, &Todo> = before.iter().map(|t| (&t.id, t)).collect();
+        let later: HashSet<&TodoID> = after.iter().map(|t| &t.id).collect();
+
+        let mut diff = Self {
+            removed: before
+                .iter()
+                .filter(|t| !later.contains(&t.id))
+                .cloned()
+                .collect(),
+            ..Self::default()
+        };
+
+        for todo in after {
+            match earlier.get(&todo.id) {
+                None => diff.added.push(todo.clone()),
+                Some(old) if todo.done && !old.done => diff.completed.push(todo.clone()),
+                Some(_) => {}
+            }
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.completed.is_empty()
+    }
+}
+
+/// Sort like [`Todos::get_all_sorted_smart`].
+pub fn sort_smart(todos: &mut [Todo]) {
+    todos.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then(b.priority.cmp(&a.priority))
+            .then(a.created_at.cmp(&b.created_at))
+    });
+}
+
+/// JSON Schemas of the types handed to the frontend, keyed by the name to generate them as.
+pub fn json_schemas() -> BTreeMap<&'static str, RootSchema> {
+    BTreeMap::from([
+        ("Todo", schema_for!(Todo)),
+        ("TodosCommandResult", schema_for!(Vec<Todo>)),
+        ("Stats", schema_for!(Stats)),
+        ("TodosWithStats", schema_for!(TodosWithStats)),
+    ])
+}
+
+/// All the todos, along with where and when they came from, e.g. for backups.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Backup {
+    pub version: u32,
+    pub exported_at: TodoTime,
+    pub format: String,
+    pub todos: Vec<Todo>,
+}
+
+impl Backup {
+    pub const FORMAT: &'static str = "mynd-todos";
+    pub const VERSION: u32 = 1;
+
+    pub fn new(todos: Vec<Todo>) -> Self {
+        Self {
+            version: Self::VERSION,
+            exported_at: TodoTime::now(),
+            format: Self::FORMAT.to_string(),
+            todos,
+        }
+    }
+
+    /// The todos, if this is a backup that can be read.
+    pub fn into_todos(self) -> anyhow::Result<Vec<Todo>> {
+        if self.format != Self::FORMAT {
+            return Err(anyhow!("not a backup of mynd todos: {:?}", self.format));
+        }
+
+        if self.version > Self::VERSION {
+            return Err(anyhow!(
+                "backup version {} is newer than supported: {}",
+                self.version,
+                Self::VERSION
+            ));
+        }
+
+        Ok(self.todos)
+    }
+}
+
+/// How imported todos are combined with the ones already in the list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    /// Add the imported todos that aren't in the list already. Todos that are keep their
+    /// current state, so e.g. re-importing an old export doesn't undo anything done since.
+    Merge,
+    /// Make the list exactly the imported todos.
+    Replace,
+}
+
+/// The whole list along with how many of them there are and are done.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TodosWithStats {
+    pub todos: Vec<Todo>,
+    pub total: usize,
+    pub done: usize,
+}
+
+/// How far along the todo list is.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Stats {
+    pub done: usize,
+    pub total: usize,
+    pub pending: usize,
+    /// The longest standing todo that isn't done yet.
+    pub oldest: Option<Todo>,
+    /// The most recently created todo that isn't done yet.
+    pub newest: Option<Todo>,
+}
+
+impl Stats {
+    /// Fraction of todos done, from 0 to 1. An empty list counts as nothing done.
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
         }
+        self.done as f64 / self.total as f64
+    }
+
+    /// A bar of `width` cells, filled in the proportion of todos done.
+    pub fn progress_bar(&self, width: usize) -> String {
+        let filled = (self.ratio() * width as f64).round() as usize;
+        "█".repeat(filled) + &"░".repeat(width - filled)
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} done ({:.0}%)",
+            self.done,
+            self.total,
+            self.ratio() * 100.0
+        )
     }
 }
 
@@ -89,66 +434,381 @@ impl Todo {
 pub struct Todos<DB: TodosDatabase> {
     list: Mutex<collection::array::TodoArrayList>,
     pub db: DB,
+    /// How many [`Todos::with_deferred_flush`] scopes are open.
+    deferring: AtomicUsize,
+    listeners: FlushListeners,
+    /// Whether adding a todo with the same message as another gives it its own id, instead of
+    /// leaving just the other one.
+    allow_duplicates: bool,
+    /// Where [`Todos::add_message`] and the like put new todos.
+    insert_at: config::InsertAt,
+}
+
+type FlushListener = Box<dyn Fn(&[Todo]) + Send + Sync>;
+
+/// Callbacks to run with the whole list, after every save.
+#[derive(Default)]
+struct FlushListeners(Mutex<Vec<FlushListener>>);
+
+impl std::fmt::Debug for FlushListeners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.0.lock().map(|l| l.len()).unwrap_or_default();
+        write!(f, "FlushListeners({count})")
+    }
 }
 
 impl<DB: TodosDatabase> Todos<DB> {
+    /// Let todos have the same message as others, see [`TodoID::hash_message_at`].
+    pub fn allowing_duplicates(mut self, allow: bool) -> Self {
+        self.allow_duplicates = allow;
+        self
+    }
+
+    /// Add new todos at the top or the bottom of the list.
+    pub fn inserting_at(mut self, insert_at: config::InsertAt) -> Self {
+        self.insert_at = insert_at;
+        self
+    }
+
     pub fn new(db: DB) -> Self {
         Self {
             list: Mutex::new(collection::array::TodoArrayList::new()),
             db,
+            deferring: AtomicUsize::new(0),
+            listeners: FlushListeners::default(),
+            allow_duplicates: false,
+            insert_at: config::InsertAt::default(),
         }
     }
 }
 
 impl Todos<ActualTodosDB> {
+    #[tracing::instrument(level = "debug")]
     pub fn load_up_with_persistor() -> Todos<ActualTodosDB> {
-        let db = ActualTodosDB::default();
-        let list = Mutex::new(TodoArrayList::from(db.get_all_todos().unwrap_or_default()));
-        Todos { list, db }
+        Self::load_up_from(ActualTodosDB::default())
+    }
+
+    fn load_up_from(db: ActualTodosDB) -> Todos<ActualTodosDB> {
+        let todos = db.get_all_todos().unwrap_or_else(|err| {
+            warn!("starting with no todos: {err:#}");
+            vec![]
+        });
+        let list = Mutex::new(TodoArrayList::from(todos));
+        let cfg = config::load_config_or_default();
+        Todos {
+            list,
+            db,
+            deferring: AtomicUsize::new(0),
+            listeners: FlushListeners::default(),
+            allow_duplicates: cfg.allow_duplicates,
+            insert_at: cfg.insert_at,
+        }
     }
 }
 
 impl<DB: TodosDatabase> Todos<DB> {
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn reload(&self) -> anyhow::Result<()> {
-        eprintln!("[TRACE] reloading todos");
         let todos = self.db.get_all_todos()?;
+        debug!("loaded {} todos", todos.len());
         *(self.inner_list()?) = TodoArrayList::from(todos);
         Ok(())
     }
 
+    /// Reload, dropping todos with an id seen earlier in the save file and numbering the rest
+    /// in order from zero, then save. Returns how many duplicates were dropped.
+    pub fn normalize(&self) -> anyhow::Result<usize> {
+        let todos = self.db.get_all_todos()?;
+        let total = todos.len();
+
+        let mut seen = HashSet::new();
+        let unique: Vec<_> = todos
+            .into_iter()
+            .filter(|t| seen.insert(t.id.clone()))
+            .collect();
+        let removed = total - unique.len();
+
+        *(self.inner_list()?) = TodoArrayList::from(unique);
+        self.flush()?;
+
+        Ok(removed)
+    }
+
+    /// Reload, giving todos whose id doesn't match their message, e.g. after editing the save file
+    /// by hand, the id their message would get now, then save. Of todos that end up with the
+    /// same id, the earliest created is kept.
+    pub fn reindex(&self) -> anyhow::Result<Reindexed> {
+        let todos = self.db.get_all_todos()?;
+        let total = todos.len();
+
+        let mut changed = 0;
+        let mut earliest: HashMap<TodoID, TodoTime> = HashMap::new();
+        let todos: Vec<_> = todos
+            .into_iter()
+            .map(|mut todo| {
+                let matches = todo.id == TodoID::hash_message(&todo.message)
+                    || todo.id == TodoID::hash_message_at(&todo.message, &todo.created_at);
+                if !matches {
+                    todo.id = if self.allow_duplicates {
+                        TodoID::hash_message_at(&todo.message, &todo.created_at)
+                    } else {
+                        TodoID::hash_message(&todo.message)
+                    };
+                    changed += 1;
+                }
+
+                let first = earliest
+                    .entry(todo.id.clone())
+                    .or_insert(todo.created_at.clone());
+                if todo.created_at < *first {
+                    *first = todo.created_at.clone();
+                }
+
+                todo
+            })
+            .collect();
+
+        let mut kept = HashSet::new();
+        let unique: Vec<_> = todos
+            .into_iter()
+            .filter(|t| earliest[&t.id] == t.created_at && kept.insert(t.id.clone()))
+            .collect();
+        let dropped = total - unique.len();
+
+        *(self.inner_list()?) = TodoArrayList::from(unique);
+        self.flush()?;
+
+        Ok(Reindexed { changed, dropped })
+    }
+
+    /// How long to wait on a contended lock before giving up, assuming a deadlock.
+    const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
     fn inner_list(&self) -> anyhow::Result<MutexGuard<TodoArrayList>> {
-        self.list
-            .try_lock()
-            .map_err(|err| anyhow!("{err}").context("failed to acquire lock on todos list"))
+        let started = Instant::now();
+
+        loop {
+            match self.list.try_lock() {
+                Ok(guard) => return Ok(guard),
+                Err(TryLockError::WouldBlock) if started.elapsed() < Self::LOCK_TIMEOUT => {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Err(err) => {
+                    return Err(anyhow!("{err}").context("failed to acquire lock on todos list"))
+                }
+            }
+        }
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn add_message(&self, message: &str) -> anyhow::Result<Todo> {
         if message.is_empty() {
             return Err(anyhow!("no sense in an empty todo message"));
         }
 
-        let todo = self.inner_list()?.add_message(message)?;
+        if self.allow_duplicates {
+            return self.add_with_time(message, TodoTime::now());
+        }
+
+        let todo = Todo::new(message.to_string());
+        self.insert_new(todo.clone())?;
+
+        Ok(todo)
+    }
+
+    /// Like [`Todos::add_message`], but as if the todo was created at `created_at`, e.g. to
+    /// backdate it.
+    pub fn add_with_time(&self, message: &str, created_at: TodoTime) -> anyhow::Result<Todo> {
+        if message.is_empty() {
+            return Err(anyhow!("no sense in an empty todo message"));
+        }
+
+        let mut todo = Todo {
+            updated_at: created_at.clone(),
+            created_at,
+            ..Todo::new(message.to_string())
+        };
+        if self.allow_duplicates {
+            todo.id = TodoID::hash_message_at(&todo.message, &todo.created_at);
+        }
+        self.insert_new(todo.clone())?;
 
         Ok(todo)
     }
 
+    /// Add a todo made just now, where [`Todos::inserting_at`] says.
+    fn insert_new(&self, todo: Todo) -> anyhow::Result<()> {
+        let mut list = self.inner_list()?;
+        let index = match self.insert_at {
+            config::InsertAt::Top => 0,
+            config::InsertAt::Bottom => list.len(),
+        };
+        list.insert_todo(index, todo);
+        Ok(())
+    }
+
     pub fn add(&self, todo: Todo) -> anyhow::Result<()> {
         self.inner_list()?.add_todo(todo);
         Ok(())
     }
 
+    /// Bring in todos from elsewhere. Returns how many were added to the list.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn import(&self, imported: Vec<Todo>, mode: ImportMode) -> anyhow::Result<usize> {
+        let mut list = self.inner_list()?;
+
+        if mode == ImportMode::Replace {
+            *list = TodoArrayList::new();
+        }
+
+        let before = list.len();
+
+        for todo in imported {
+            list.add_todo(todo);
+        }
+
+        Ok(list.len() - before)
+    }
+
+    /// Reconcile with todos from another store, e.g. another machine's save file.
+    ///
+    /// Todos are matched by id. Todos only in `other` are appended, keeping their order in
+    /// `other`, after this store's todos. When both have a todo with the same id, the more
+    /// recently updated copy wins, keeping this store's position for it; if neither is newer,
+    /// a done todo wins over one that isn't.
+    /// Returns how many todos were added or changed.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn merge(&self, other: Vec<Todo>) -> anyhow::Result<usize> {
+        let mut list = self.inner_list()?;
+        let mut changed = 0;
+
+        for mut theirs in other {
+            let Ok(ours) = list.get(&theirs.id.0) else {
+                list.add_todo(theirs);
+                changed += 1;
+                continue;
+            };
+
+            theirs.backfill_updated_at();
+
+            let merged = if theirs.updated_at > ours.updated_at {
+                theirs
+            } else if theirs.updated_at == ours.updated_at && theirs.done && !ours.done {
+                Todo { done: true, ..ours }
+            } else {
+                continue;
+            };
+
+            list.update(merged)?;
+            changed += 1;
+        }
+
+        Ok(changed)
+    }
+
+    /// Take in the todos of a document in the todo language, read with the keywords and such
+    /// from the config. See [`Todos::apply_todolang_text`].
+    pub fn apply_todolang(&self, text: &str) -> anyhow::Result<TodolangApplied> {
+        let cfg = config::load_config_or_default();
+        self.apply_todolang_text(lang::parse(text, &cfg))
+    }
+
+    /// Take in the todos of a parsed todo language document. Each is moved below the ones
+    /// before it, keeping what's known of it, or added if it's new; a `[x]` or `[ ]` on it
+    /// marks it done or not. Todos that aren't in the document are left alone.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn apply_todolang_text(
+        &self,
+        text: lang::parser::ast::Text,
+    ) -> anyhow::Result<TodolangApplied> {
+        // the save file too, to bring back a todo that an earlier edit took out
+        let known: HashMap<TodoID, Todo> = self
+            .db
+            .get_all_todos()
+            .context("failed to read the saved todos")?
+            .into_iter()
+            .chain(self.get_all()?)
+            .map(|t| (t.id.clone(), t))
+            .collect();
+
+        let mut applied = TodolangApplied::default();
+        let mut list = self.inner_list()?;
+
+        for item in text.items {
+            let item = match item {
+                Ok(lang::parser::ast::Item::OneLine(t) | lang::parser::ast::Item::Multiline(t)) => {
+                    t
+                }
+                Err(err) => {
+                    applied.errors.push(err);
+                    continue;
+                }
+            };
+
+            let id = TodoID::hash_message(&item.message);
+            let todo = known
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| Todo::new(item.message));
+
+            if list.contains(&id) {
+                list.remove(&id.0)?;
+            }
+            list.add_todo(todo);
+
+            // otherwise it stays as it was, or pending when new
+            if let Some(done) = item.done {
+                list.set_done(&id.0, done)?;
+            }
+
+            applied.ids.push(id);
+        }
+
+        Ok(applied)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn remove(&self, id: &str) -> anyhow::Result<()> {
         self.inner_list()?.remove(id)?;
 
-        eprintln!("[INFO] removed a todo item");
+        debug!("removed a todo item");
 
         Ok(())
     }
 
-    pub fn mark_done(&self, id: &str) -> anyhow::Result<()> {
-        self.inner_list()?.mark_done(id)?;
+    /// Toggle whether a todo is done. Returns whether the done flag changed.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn mark_done(&self, id: &str) -> anyhow::Result<bool> {
+        self.inner_list()?.mark_done(id)
+    }
 
-        Ok(())
+    /// Set whether a todo is done. Returns false if it already was in that state.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn set_done(&self, id: &str, done: bool) -> anyhow::Result<bool> {
+        self.inner_list()?.set_done(id, done)
+    }
+
+    /// Mark done every todo whose message contains the query. Returns how many changed.
+    pub fn mark_done_matching(&self, query: &str) -> anyhow::Result<usize> {
+        let mut list = self.inner_list()?;
+        let mut count = 0;
+
+        for todo in Self::matching(&list.get_all(), query) {
+            if list.set_done(&todo.id.0, true)? {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Delete every todo, done or not, returning how many there were.
+    pub fn clear(&self) -> anyhow::Result<usize> {
+        let removed = std::mem::replace(&mut *self.inner_list()?, TodoArrayList::new()).len();
+        debug!("cleared {} todo items", removed);
+        self.flush()?;
+
+        Ok(removed)
     }
 
     pub fn remove_done(&self) -> anyhow::Result<()> {
@@ -177,107 +837,1476 @@ impl<DB: TodosDatabase> Todos<DB> {
     pub fn move_below(&self, id: &str, target_id: &str) -> anyhow::Result<()> {
         self.inner_list()?.move_below(id, target_id)?;
 
-        eprintln!("[INFO] move a todo item below another");
+        debug!("moved a todo item below another");
 
         self.flush()?;
 
         Ok(())
     }
 
-    pub fn get_all(&self) -> anyhow::Result<Vec<Todo>> {
-        let all = self.inner_list()?.get_all();
-        eprintln!("[TRACE] getting all {} todos", all.len());
-        Ok(all)
+    /// Move a todo to end up at the given index, or the end of the list if that's past it.
+    pub fn reorder(&self, id: &str, to_index: usize) -> anyhow::Result<()> {
+        self.inner_list()?.reorder(id, to_index)?;
+
+        debug!("moved a todo item to index {}", to_index);
+
+        self.flush()?;
+
+        Ok(())
     }
 
-    pub fn flush(&self) -> anyhow::Result<Vec<Todo>> {
-        let all = self.get_all()?;
-        self.db.set_all_todos(all.clone())?;
-        Ok(all)
+    /// Todos created on the given day, in local time.
+    pub fn on_day(&self, date: NaiveDate) -> anyhow::Result<Vec<Todo>> {
+        self.on_day_in(date, &Local)
     }
-}
 
-pub mod inmem {
-    use super::*;
+    /// Todos created on the given day, in the given timezone.
+    pub fn on_day_in<Tz: TimeZone>(&self, date: NaiveDate, tz: &Tz) -> anyhow::Result<Vec<Todo>> {
+        Ok(self
+            .get_all()?
+            .into_iter()
+            .filter(|t| t.created_at.date_in(tz) == date)
+            .collect())
+    }
 
-    pub struct NoopDB;
+    /// Move todos to the top together, keeping the order they were in, e.g. a multi-selection.
+    pub fn move_many_to_top(&self, ids: &[&str]) -> anyhow::Result<()> {
+        self.inner_list()?.move_many_to_top(ids)?;
 
-    impl TodosDatabase for NoopDB {
-        fn get_all_todos(&self) -> anyhow::Result<Vec<Todo>> {
-            return Ok(vec![]);
-        }
+        debug!("moved {} todo items to the top", ids.len());
 
-        fn set_all_todos(&self, _todos: Vec<Todo>) -> anyhow::Result<()> {
-            Ok(())
-        }
-    }
+        self.flush()?;
 
-    impl Todos<NoopDB> {
-        pub fn new_inmemory() -> Todos<NoopDB> {
-            Todos::new(NoopDB)
-        }
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Put the todos in the order of `ordered_ids`, e.g. after a drag and drop, saving once.
+    pub fn set_order(&self, ordered_ids: &[String]) -> anyhow::Result<()> {
+        self.inner_list()?.set_order(ordered_ids)?;
 
-    use super::*;
+        debug!("set the order of {} todo items", ordered_ids.len());
 
-    #[test]
-    fn move_below_from_top_to_bottom() {
-        let todos = Todos::new_inmemory();
+        self.flush()?;
 
-        todos.add_message("1").unwrap();
-        todos.add_message("2").unwrap();
-        let target = todos.add_message("3").unwrap().id.0;
-        todos.add_message("4").unwrap();
-        let id = todos.add_message("5").unwrap().id.0;
-        // now, todos = [5, 4, 3, 2, 1]
+        Ok(())
+    }
 
-        todos.move_below(&id, &target).unwrap();
+    /// Move todos to the bottom together, keeping the order they were in.
+    pub fn move_many_to_bottom(&self, ids: &[&str]) -> anyhow::Result<()> {
+        self.inner_list()?.move_many_to_bottom(ids)?;
 
-        let messages = todos
-            .get_all()
-            .unwrap()
-            .into_iter()
-            .map(|t| t.message)
-            .collect::<Vec<_>>();
+        debug!("moved {} todo items to the bottom", ids.len());
 
-        assert_eq!(
-            messages,
-            vec![
-                "1".to_string(),
-                "2".to_string(),
-                "3".to_string(),
-                "5".to_string(),
-                "4".to_string(),
-            ]
-        )
+        self.flush()?;
+
+        Ok(())
     }
 
-    #[test]
-    fn move_below_from_bottom_to_top() {
+    /// Move the done todos below the pending ones, keeping the order within each.
+    pub fn sink_done(&self) -> anyhow::Result<()> {
+        {
+            let mut list = self.inner_list()?;
+            let done: Vec<String> = list
+                .iter()
+                .filter(|t| t.done)
+                .map(|t| t.id.0.to_string())
+                .collect();
+            let done: Vec<&str> = done.iter().map(String::as_str).collect();
+            list.move_many_to_bottom(&done)?;
+
+            debug!("sank {} done todo items", done.len());
+        }
+
+        self.flush()?;
+
+        Ok(())
+    }
+
+    /// Pin or unpin a todo. Returns whether it's pinned now.
+    pub fn toggle_pin(&self, id: &str) -> anyhow::Result<bool> {
+        self.inner_list()?.toggle_pin(id)
+    }
+
+    /// Returns whether the priority changed.
+    pub fn set_priority(&self, id: &str, priority: u8) -> anyhow::Result<bool> {
+        self.inner_list()?.set_priority(id, priority)
+    }
+
+    /// All the todos, bottom of the list first.
+    pub fn get_all_reversed(&self) -> anyhow::Result<Vec<Todo>> {
+        let mut all = self.get_all()?;
+        all.reverse();
+        Ok(all)
+    }
+
+    /// All the todos, pinned ones first, then most urgent first, then oldest first so that
+    /// stale todos surface.
+    pub fn get_all_sorted_smart(&self) -> anyhow::Result<Vec<Todo>> {
+        let mut all = self.get_all()?;
+        sort_smart(&mut all);
+        Ok(all)
+    }
+
+    pub fn get(&self, id: &str) -> anyhow::Result<Todo> {
+        self.inner_list()?
+            .get(id)
+            .with_context(|| format!("failed to get todo id: {id}"))
+    }
+
+    /// Find todos whose message contains the query, ignoring case.
+    pub fn search(&self, query: &str) -> anyhow::Result<Vec<Todo>> {
+        Ok(Self::matching(&self.get_all()?, query))
+    }
+
+    fn matching(todos: &[Todo], query: &str) -> Vec<Todo> {
+        let query = query.to_lowercase();
+        todos
+            .iter()
+            .filter(|t| t.message.to_lowercase().contains(&query))
+            .cloned()
+            .collect()
+    }
+
+    /// The longest standing todo that isn't done yet.
+    pub fn oldest(&self) -> anyhow::Result<Option<Todo>> {
+        let list = self.inner_list()?;
+        Ok(list
+            .iter()
+            .filter(|t| !t.done)
+            .min_by(|a, b| a.created_at.cmp(&b.created_at))
+            .cloned())
+    }
+
+    /// The most recently created todo that isn't done yet.
+    pub fn newest(&self) -> anyhow::Result<Option<Todo>> {
+        let list = self.inner_list()?;
+        Ok(list
+            .iter()
+            .filter(|t| !t.done)
+            .max_by(|a, b| a.created_at.cmp(&b.created_at))
+            .cloned())
+    }
+
+    pub fn stats(&self) -> anyhow::Result<Stats> {
+        Ok(Self::stats_of(&*self.inner_list()?))
+    }
+
+    /// All the todos, and the counts for exactly those, taken under one lock on the list.
+    pub fn get_all_with_stats(&self) -> anyhow::Result<TodosWithStats> {
+        let list = self.inner_list()?;
+        let Stats { total, done, .. } = Self::stats_of(&*list);
+
+        Ok(TodosWithStats {
+            todos: list.get_all(),
+            total,
+            done,
+        })
+    }
+
+    fn stats_of(list: &impl TodoCollection) -> Stats {
+        let pending = || list.iter().filter(|t| !t.done);
+        let done = list.len() - pending().count();
+
+        Stats {
+            done,
+            total: list.len(),
+            pending: list.len() - done,
+            oldest: pending()
+                .min_by(|a, b| a.created_at.cmp(&b.created_at))
+                .cloned(),
+            newest: pending()
+                .max_by(|a, b| a.created_at.cmp(&b.created_at))
+                .cloned(),
+        }
+    }
+
+    /// Look over the todos in order without cloning them, holding the lock on the list while
+    /// `f` runs.
+    pub fn with_iter<R>(
+        &self,
+        f: impl FnOnce(&mut dyn Iterator<Item = &Todo>) -> R,
+    ) -> anyhow::Result<R> {
+        let list = self.inner_list()?;
+        let result = f(&mut list.iter());
+        Ok(result)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn get_all(&self) -> anyhow::Result<Vec<Todo>> {
+        let all = self.inner_list()?.get_all();
+        trace!("getting all {} todos", all.len());
+        Ok(all)
+    }
+
+    /// Save the todos. Inside [`Todos::with_deferred_flush`], this waits for the scope to end.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn flush(&self) -> anyhow::Result<Vec<Todo>> {
+        let all = self.get_all()?;
+
+        if self.deferring.load(Ordering::SeqCst) > 0 {
+            debug!("deferring the save");
+            return Ok(all);
+        }
+
+        self.db.set_all_todos(all.clone())?;
+        debug!("saved {} todos", all.len());
+
+        for listener in self.listeners.0.lock().unwrap().iter() {
+            listener(&all);
+        }
+
+        Ok(all)
+    }
+
+    /// Call `listener` with all the todos whenever they're saved, e.g. to tell a UI about changes
+    /// it didn't make itself.
+    pub fn on_flush(&self, listener: impl Fn(&[Todo]) + Send + Sync + 'static) {
+        self.listeners.0.lock().unwrap().push(Box::new(listener));
+    }
+
+    /// Run `f` and only then flush, once, however many flushes happened along the way.
+    /// Nothing is flushed if `f` fails.
+    pub fn with_deferred_flush<T>(
+        &self,
+        f: impl FnOnce(&Self) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        struct Deferring<'a>(&'a AtomicUsize);
+
+        impl Drop for Deferring<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        let result = {
+            self.deferring.fetch_add(1, Ordering::SeqCst);
+            let _scope = Deferring(&self.deferring);
+            f(self)?
+        };
+
+        self.flush()?;
+
+        Ok(result)
+    }
+}
+
+/// Databases for tests that care how the database is used.
+#[doc(hidden)]
+pub mod testing {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    /// Keeps what's logged, as the writer of a `tracing_subscriber::fmt` subscriber.
+    #[derive(Clone, Default)]
+    pub struct Logs(Arc<Mutex<Vec<u8>>>);
+
+    impl Logs {
+        pub fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    impl std::io::Write for Logs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for Logs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// The todo messages, in order, for comparing lists in tests.
+    pub fn messages<DB: TodosDatabase>(todos: &Todos<DB>) -> Vec<String> {
+        todos
+            .get_all()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.message)
+            .collect()
+    }
+
+    /// Keeps nothing, but counts the saves.
+    #[derive(Default)]
+    pub struct CountingDB(AtomicUsize);
+
+    impl CountingDB {
+        pub fn saves(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    impl TodosDatabase for CountingDB {
+        fn get_all_todos(&self) -> anyhow::Result<Vec<Todo>> {
+            Ok(vec![])
+        }
+
+        fn set_all_todos(&self, _todos: Vec<Todo>) -> anyhow::Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    pub use crate::inmem::MemoryDB;
+}
+
+pub mod inmem {
+    use super::*;
+
+    pub struct NoopDB;
+
+    impl TodosDatabase for NoopDB {
+        fn get_all_todos(&self) -> anyhow::Result<Vec<Todo>> {
+            return Ok(vec![]);
+        }
+
+        fn set_all_todos(&self, _todos: Vec<Todo>) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Keeps todos in memory, exactly as given, like a file someone edited by hand.
+    #[derive(Debug, Default)]
+    pub struct MemoryDB(Mutex<Vec<Todo>>);
+
+    impl MemoryDB {
+        pub fn with(todos: Vec<Todo>) -> Self {
+            Self(Mutex::new(todos))
+        }
+    }
+
+    impl TodosDatabase for MemoryDB {
+        fn get_all_todos(&self) -> anyhow::Result<Vec<Todo>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn set_all_todos(&self, todos: Vec<Todo>) -> anyhow::Result<()> {
+            *self.0.lock().unwrap() = todos;
+            Ok(())
+        }
+    }
+
+    impl Todos<NoopDB> {
+        pub fn new_inmemory() -> Todos<NoopDB> {
+            Todos::new(NoopDB)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::testing::messages;
+
+    #[test]
+    fn todo_schema_has_all_properties() {
+        let schemas = json_schemas();
+        let todo = serde_json::to_value(&schemas["Todo"]).unwrap();
+        let properties = todo["properties"].as_object().unwrap();
+
+        for name in ["id", "message", "created_at", "done", "updated_at"] {
+            assert!(properties.contains_key(name), "missing property: {name}");
+        }
+
+        let result = serde_json::to_value(&schemas["TodosCommandResult"]).unwrap();
+        assert_eq!(result["type"], "array");
+    }
+
+    #[test]
+    fn concurrent_adds_dont_fail_on_contention() {
+        let todos = Todos::new_inmemory();
+
+        std::thread::scope(|s| {
+            for thread in 0..8 {
+                let todos = &todos;
+                s.spawn(move || {
+                    for i in 0..50 {
+                        todos.add_message(&format!("{thread}-{i}")).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(todos.get_all().unwrap().len(), 400);
+    }
+
+    #[test]
+    fn mark_done_reports_change() {
+        let todos = Todos::new_inmemory();
+        let id = todos.add_message("1").unwrap().id.0;
+
+        assert!(todos.mark_done(&id).unwrap());
+        assert!(todos.get_all().unwrap()[0].done);
+
+        assert!(!todos.set_done(&id, true).unwrap());
+        assert!(todos.set_done(&id, false).unwrap());
+        assert!(!todos.get_all().unwrap()[0].done);
+
+        assert!(todos.mark_done("missing").is_err());
+        assert!(todos.set_done("missing", true).is_err());
+    }
+
+    #[test]
+    fn get_by_id() {
+        let todos = Todos::new_inmemory();
+        todos.add_message("1").unwrap();
+        let id = todos.add_message("2").unwrap().id.0;
+
+        assert_eq!(todos.get(&id).unwrap().message, "2");
+
+        let err = todos.get("missing").unwrap_err();
+        assert!(format!("{err:#}").contains("failed to get todo id: missing"));
+    }
+
+    #[test]
+    fn mark_done_matching_query() {
+        let todos = Todos::new_inmemory();
+        todos.add_message("review the PR").unwrap();
+        todos.add_message("write docs").unwrap();
+        todos.add_message("Review the design").unwrap();
+
+        assert_eq!(todos.search("review").unwrap().len(), 2);
+        assert_eq!(todos.mark_done_matching("review").unwrap(), 2);
+
+        let done = todos
+            .get_all()
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.message, t.done))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            done,
+            vec![
+                ("review the PR".to_string(), true),
+                ("write docs".to_string(), false),
+                ("Review the design".to_string(), true),
+            ]
+        );
+
+        assert_eq!(todos.mark_done_matching("review").unwrap(), 0);
+    }
+
+    #[test]
+    fn mark_done_keeps_while_remove_deletes() {
+        let todos = Todos::new_inmemory();
+        let done = todos.add_message("1").unwrap().id;
+        let removed = todos.add_message("2").unwrap().id;
+
+        todos.mark_done(&done.0).unwrap();
+        todos.remove(&removed.0).unwrap();
+
+        let all = todos.get_all().unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, done);
+        assert!(all[0].done);
+    }
+
+    #[test]
+    fn import_merges_or_replaces() {
+        let todos = Todos::new_inmemory();
+        todos.add_message("1").unwrap();
+        todos.add_message("2").unwrap();
+
+        let imported = vec![Todo::new("2".to_string()), Todo::new("3".to_string())];
+
+        assert_eq!(
+            todos.import(imported.clone(), ImportMode::Merge).unwrap(),
+            1
+        );
+        assert_eq!(messages(&todos), vec!["1", "2", "3"]);
+
+        assert_eq!(todos.import(imported, ImportMode::Replace).unwrap(), 2);
+        assert_eq!(messages(&todos), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn merge_disjoint_lists() {
+        let todos = Todos::new_inmemory();
+        todos.add_message("1").unwrap();
+
+        let other = vec![Todo::new("2".to_string()), Todo::new("3".to_string())];
+
+        assert_eq!(todos.merge(other).unwrap(), 2);
+        assert_eq!(messages(&todos), vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn merge_overlapping_identical() {
+        let todos = Todos::new_inmemory();
+        todos.add_message("1").unwrap();
+        todos.add_message("2").unwrap();
+
+        let before = todos.get_all().unwrap();
+
+        assert_eq!(todos.merge(before.clone()).unwrap(), 0);
+        assert_eq!(todos.get_all().unwrap(), before);
+    }
+
+    #[test]
+    fn merge_overlapping_divergent() {
+        let todos = Todos::new_inmemory();
+        let one = todos.add_message("1").unwrap();
+        let two = todos.add_message("2").unwrap();
+        todos.mark_done(&two.id.0).unwrap();
+
+        let mut other_one = one.clone();
+        other_one.done = true;
+        other_one.message = "1 edited elsewhere".to_string();
+        let other_two = Todo { done: false, ..two };
+
+        assert_eq!(todos.merge(vec![other_two, other_one]).unwrap(), 1);
+
+        let merged = todos.get_all().unwrap();
+        assert_eq!(
+            merged
+                .iter()
+                .map(|t| (t.message.as_str(), t.done))
+                .collect::<Vec<_>>(),
+            vec![("1", true), ("2", true)]
+        );
+    }
+
+    #[test]
+    fn stats_ratio_and_bar() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3", "4", "5"] {
+            todos.add_message(message).unwrap();
+        }
+        todos.mark_done_matching("2").unwrap();
+        todos.mark_done_matching("4").unwrap();
+
+        let stats = todos.stats().unwrap();
+
+        assert_eq!((stats.done, stats.total, stats.pending), (2, 5, 3));
+        assert_eq!(stats.ratio(), 0.4);
+        assert_eq!(stats.progress_bar(10), "████░░░░░░");
+        assert_eq!(stats.to_string(), "2/5 done (40%)");
+
+        let empty = Todos::new_inmemory().stats().unwrap();
+        assert_eq!(empty.progress_bar(4), "░░░░");
+        assert_eq!(empty.to_string(), "0/0 done (0%)");
+    }
+
+    #[test]
+    fn selects_oldest_and_newest() {
+        let todos = Todos::new_inmemory();
+        let at = |message: &str, time: &str| Todo {
+            created_at: time.parse().unwrap(),
+            ..Todo::new(message.to_string())
+        };
+
+        todos.add(at("middle", "2024-02-01T00:00:00Z")).unwrap();
+        todos.add(at("newest", "2024-03-01T00:00:00Z")).unwrap();
+        todos.add(at("oldest", "2024-01-01T00:00:00Z")).unwrap();
+
+        assert_eq!(todos.oldest().unwrap().unwrap().message, "oldest");
+        assert_eq!(todos.newest().unwrap().unwrap().message, "newest");
+
+        todos.mark_done_matching("oldest").unwrap();
+        assert_eq!(todos.oldest().unwrap().unwrap().message, "middle");
+
+        assert!(Todos::new_inmemory().oldest().unwrap().is_none());
+    }
+
+    #[test]
+    fn diffs_lists_by_id() {
+        let kept = Todo::new("kept".to_string());
+        let finished = Todo::new("finished".to_string());
+        let dropped = Todo::new("dropped".to_string());
+        let already_done = Todo {
+            done: true,
+            ..Todo::new("already done".to_string())
+        };
+        let before = vec![
+            kept.clone(),
+            finished.clone(),
+            dropped.clone(),
+            already_done.clone(),
+        ];
+
+        let new = Todo::new("new".to_string());
+        let after = vec![
+            new.clone(),
+            kept.clone(),
+            Todo {
+                done: true,
+                ..finished.clone()
+            },
+            already_done,
+        ];
+
+        let diff = ListDiff::between(&before, &after);
+        let ids = |todos: &[Todo]| todos.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.added), vec![new.id]);
+        assert_eq!(ids(&diff.removed), vec![dropped.id]);
+        assert_eq!(ids(&diff.completed), vec![finished.id]);
+
+        assert!(ListDiff::between(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn get_all_reversed_is_bottom_up() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3"] {
+            todos.add_message(message).unwrap();
+        }
+
+        let messages: Vec<_> = todos
+            .get_all_reversed()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.message)
+            .collect();
+        assert_eq!(messages, vec!["3", "2", "1"]);
+    }
+
+    #[test]
+    fn sinking_done_keeps_each_group_in_order() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3", "4", "5", "6"] {
+            todos.add_message(message).unwrap();
+        }
+        for message in ["1", "4", "5"] {
+            todos.mark_done_matching(message).unwrap();
+        }
+
+        todos.sink_done().unwrap();
+
+        let all = todos.get_all().unwrap();
+        assert_eq!(
+            all.iter()
+                .map(|t| (t.message.as_str(), t.done))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2", false),
+                ("3", false),
+                ("6", false),
+                ("1", true),
+                ("4", true),
+                ("5", true)
+            ]
+        );
+        assert!(all.iter().enumerate().all(|(i, t)| t.order == i as u32));
+    }
+
+    #[test]
+    fn todos_with_stats_agree() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3"] {
+            todos.add_message(message).unwrap();
+        }
+        todos.mark_done_matching("3").unwrap();
+
+        let loaded = todos.get_all_with_stats().unwrap();
+        assert_eq!(loaded.todos, todos.get_all().unwrap());
+        assert_eq!((loaded.total, loaded.done), (3, 1));
+
+        let json = serde_json::to_value(&loaded).unwrap();
+        assert_eq!(json["todos"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            (json["total"].as_u64(), json["done"].as_u64()),
+            (Some(3), Some(1))
+        );
+    }
+
+    #[test]
+    fn set_order_follows_the_given_ids() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3"] {
+            todos.add_message(message).unwrap();
+        }
+        let ids = |order: [usize; 3]| {
+            let all = todos.get_all().unwrap();
+            order.map(|i| all[i].id.0.to_string()).to_vec()
+        };
+
+        todos.set_order(&ids([2, 0, 1])).unwrap();
+        assert_eq!(
+            todos
+                .get_all()
+                .unwrap()
+                .iter()
+                .map(|t| (t.message.as_str(), t.order))
+                .collect::<Vec<_>>(),
+            vec![("3", 0), ("1", 1), ("2", 2)]
+        );
+    }
+
+    #[test]
+    fn set_order_needs_each_id_once() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3"] {
+            todos.add_message(message).unwrap();
+        }
+        let before = todos.get_all().unwrap();
+        let id = |i: usize| before[i].id.0.to_string();
+
+        let missing = todos.set_order(&[id(0), id(1)]).unwrap_err();
+        assert!(missing.to_string().contains("got 2 ids to order 3 todos"));
+
+        let repeated = todos.set_order(&[id(0), id(1), id(1)]).unwrap_err();
+        assert!(repeated.to_string().contains("more than once"));
+
+        let unknown = todos
+            .set_order(&[id(0), id(1), "not-a-todo".to_string()])
+            .unwrap_err();
+        assert!(unknown.to_string().contains("not-a-todo"));
+
+        assert_eq!(todos.get_all().unwrap(), before);
+    }
+
+    #[test]
+    fn iter_yields_what_get_all_does() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3"] {
+            todos.add_message(message).unwrap();
+        }
+        todos.mark_done_matching("2").unwrap();
+        let last = todos.get_all().unwrap()[2].id.0.to_string();
+        todos.move_up(last).unwrap();
+
+        let list = todos.inner_list().unwrap();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), list.get_all());
+        drop(list);
+
+        let borrowed = todos.with_iter(|all| all.cloned().collect::<Vec<_>>());
+        assert_eq!(borrowed.unwrap(), todos.get_all().unwrap());
+    }
+
+    #[test]
+    fn stats_for_a_known_list() {
+        let todos = Todos::new_inmemory();
+        let at = |message: &str, time: &str| Todo {
+            created_at: time.parse().unwrap(),
+            ..Todo::new(message.to_string())
+        };
+
+        todos.add(at("first", "2024-01-01T00:00:00Z")).unwrap();
+        todos.add(at("second", "2024-02-01T00:00:00Z")).unwrap();
+        todos.add(at("third", "2024-03-01T00:00:00Z")).unwrap();
+        todos.add(at("fourth", "2024-04-01T00:00:00Z")).unwrap();
+        todos.mark_done_matching("first").unwrap();
+        todos.mark_done_matching("fourth").unwrap();
+
+        let stats = todos.stats().unwrap();
+        assert_eq!((stats.done, stats.total, stats.pending), (2, 4, 2));
+        assert_eq!(stats.oldest.unwrap().message, "second");
+        assert_eq!(stats.newest.unwrap().message, "third");
+
+        let json = serde_json::to_value(todos.stats().unwrap()).unwrap();
+        for name in ["total", "done", "pending", "oldest", "newest"] {
+            assert!(json.get(name).is_some(), "missing field: {name}");
+        }
+
+        let empty = Todos::new_inmemory().stats().unwrap();
+        assert!(empty.oldest.is_none() && empty.newest.is_none());
+    }
+
+    #[test]
+    fn normalize_dedupes_and_renumbers() {
+        let todo = |message: &str, order| Todo {
+            order,
+            ..Todo::new(message.to_string())
+        };
+        let first = todo("1", 3);
+        let duplicate = Todo {
+            done: true,
+            ..todo("1", 20)
+        };
+
+        let todos = Todos::new(testing::MemoryDB::with(vec![
+            todo("2", 10),
+            first.clone(),
+            todo("3", 7),
+            duplicate,
+        ]));
+
+        assert_eq!(todos.normalize().unwrap(), 1);
+
+        let saved = todos.db.get_all_todos().unwrap();
+        let summary = saved
+            .iter()
+            .map(|t| (t.message.as_str(), t.order, t.done))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![("1", 0, false), ("3", 1, false), ("2", 2, false)]
+        );
+        assert_eq!(todos.get_all().unwrap().len(), saved.len());
+
+        assert_eq!(todos.normalize().unwrap(), 0);
+    }
+
+    #[test]
+    fn reindex_fixes_stale_ids() {
+        let at = |day: u32| TodoTime(Utc.with_ymd_and_hms(2024, 6, day, 12, 0, 0).unwrap());
+        let edited = Todo {
+            id: TodoID::hash_message("befor edit"),
+            created_at: at(2),
+            ..Todo::new("before edit".to_string())
+        };
+        let clashing = Todo {
+            id: "stale".into(),
+            created_at: at(1),
+            ..Todo::new("already here".to_string())
+        };
+        let todos = Todos::new(testing::MemoryDB::with(vec![
+            Todo {
+                created_at: at(3),
+                ..Todo::new("already here".to_string())
+            },
+            edited,
+            clashing,
+        ]));
+
+        assert_eq!(
+            todos.reindex().unwrap(),
+            Reindexed {
+                changed: 2,
+                dropped: 1
+            }
+        );
+
+        let saved = todos.db.get_all_todos().unwrap();
+        let summary = saved
+            .iter()
+            .map(|t| (t.message.as_str(), t.id.clone(), t.created_at.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("before edit", TodoID::hash_message("before edit"), at(2)),
+                ("already here", TodoID::hash_message("already here"), at(1)),
+            ]
+        );
+
+        assert_eq!(
+            todos.reindex().unwrap(),
+            Reindexed {
+                changed: 0,
+                dropped: 0
+            }
+        );
+    }
+
+    #[test]
+    fn duplicates_only_when_allowed() {
+        let todos = Todos::new_inmemory();
+        let first = todos.add_message("same").unwrap();
+        let second = todos.add_message("same").unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(todos.get_all().unwrap().len(), 1);
+
+        let todos = Todos::new_inmemory().allowing_duplicates(true);
+        let first = todos.add_message("same").unwrap();
+        let second = todos.add_message("same").unwrap();
+        assert_ne!(first.id, second.id);
+        assert_eq!(todos.get_all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn adds_at_the_configured_end() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3"] {
+            todos.add_message(message).unwrap();
+        }
+        assert_eq!(messages(&todos), ["1", "2", "3"]);
+
+        let todos = Todos::new_inmemory().inserting_at(config::InsertAt::Top);
+        for message in ["1", "2", "3"] {
+            todos.add_message(message).unwrap();
+        }
+        assert_eq!(messages(&todos), ["3", "2", "1"]);
+
+        // still below the pinned ones, and moving works off the new order
+        todos.toggle_pin(&TodoID::hash_message("1").0).unwrap();
+        todos.add_with_time("4", TodoTime::now()).unwrap();
+        assert_eq!(messages(&todos), ["1", "4", "3", "2"]);
+        todos
+            .move_down(TodoID::hash_message("4").0.to_string())
+            .unwrap();
+        assert_eq!(messages(&todos), ["1", "3", "4", "2"]);
+    }
+
+    #[test]
+    fn move_many_keeps_their_order() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3", "4", "5", "6"] {
+            todos.add_message(message).unwrap();
+        }
+        let id = |message| TodoID::hash_message(message).0;
+        todos
+            .move_many_to_top(&[&id("5"), &id("2"), &id("4")])
+            .unwrap();
+        assert_eq!(messages(&todos), vec!["2", "4", "5", "1", "3", "6"]);
+
+        todos.move_many_to_bottom(&[&id("4"), &id("2")]).unwrap();
+        assert_eq!(messages(&todos), vec!["5", "1", "3", "6", "2", "4"]);
+
+        assert!(todos.move_many_to_top(&[&id("1"), "nope"]).is_err());
+        assert_eq!(messages(&todos), vec!["5", "1", "3", "6", "2", "4"]);
+    }
+
+    #[test]
+    fn reorder_to_index() {
+        let todos = Todos::new_inmemory();
+
+        todos.add_message("1").unwrap();
+        let two = todos.add_message("2").unwrap().id.0;
+        todos.add_message("3").unwrap();
+
+        todos.reorder(&two, 0).unwrap();
+        assert_eq!(messages(&todos), vec!["2", "1", "3"]);
+
+        todos.reorder(&two, 2).unwrap();
+        assert_eq!(messages(&todos), vec!["1", "3", "2"]);
+
+        todos.reorder(&two, 0).unwrap();
+        todos.reorder(&two, 100).unwrap();
+        assert_eq!(messages(&todos), vec!["1", "3", "2"]);
+
+        assert!(todos.reorder("nope", 0).is_err());
+    }
+
+    #[test]
+    fn merge_prefers_newer() {
+        let todos = Todos::new_inmemory();
+        let one = todos.add_message("1").unwrap();
+
+        let mut theirs = one.clone();
+        theirs.pinned = true;
+        theirs.updated_at = "2200-01-01T00:00:00Z".parse().unwrap();
+
+        assert_eq!(todos.merge(vec![theirs.clone()]).unwrap(), 1);
+        assert!(todos.get(&one.id.0).unwrap().pinned);
+
+        // but not older
+        let stale = Todo { done: true, ..one };
+        assert_eq!(todos.merge(vec![stale]).unwrap(), 0);
+        assert!(!todos.get(&theirs.id.0).unwrap().done);
+    }
+
+    #[test]
+    fn updated_at_advances_on_changes_only() {
+        let todos = Todos::new_inmemory();
+        let one = todos.add_message("1").unwrap();
+        todos.add_message("2").unwrap();
+        assert_eq!(one.updated_at, one.created_at);
+
+        let updated_at = || todos.get(&one.id.0).unwrap().updated_at;
+
+        let _ = todos.get_all().unwrap();
+        let _ = todos.search("1").unwrap();
+        assert_eq!(updated_at(), one.created_at);
+
+        let mut last = updated_at();
+        let mut assert_advanced = |change: &dyn Fn()| {
+            std::thread::sleep(Duration::from_millis(1));
+            change();
+            assert!(updated_at() > last);
+            last = updated_at();
+        };
+
+        assert_advanced(&|| {
+            todos.mark_done(&one.id.0).unwrap();
+        });
+        assert_advanced(&|| {
+            todos.set_done(&one.id.0, false).unwrap();
+        });
+        assert_advanced(&|| todos.reorder(&one.id.0, 1).unwrap());
+        assert_advanced(&|| todos.move_up(one.id.0.to_string()).unwrap());
+        assert_advanced(&|| {
+            todos.toggle_pin(&one.id.0).unwrap();
+        });
+
+        // setting done to what it already is, isn't a change
+        let before = updated_at();
+        todos.set_done(&one.id.0, false).unwrap();
+        assert_eq!(updated_at(), before);
+    }
+
+    #[test]
+    fn old_todos_were_updated_when_created() {
+        let json = r#"[{"id": "x", "message": "old", "created_at": "2024-01-01T00:00:00Z", "done": false}]"#;
+        let stored: Vec<Todo> = serde_json::from_str(json).unwrap();
+
+        let loaded = TodoArrayList::from(stored).get_all();
+        assert_eq!(loaded[0].updated_at, loaded[0].created_at);
+    }
+
+    #[test]
+    fn deferred_flush_saves_once() {
+        let todos = Todos::new(testing::CountingDB::default());
+
+        todos
+            .with_deferred_flush(|todos| {
+                let one = todos.add_message("1")?;
+                todos.add_message("2")?;
+                todos.flush()?;
+                todos.move_below(&one.id.0, &TodoID::hash_message("2").0)?;
+                todos.with_deferred_flush(|todos| todos.remove_done())?;
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(todos.db.saves(), 1);
+
+        let failed = todos.with_deferred_flush(|todos| {
+            todos.flush()?;
+            Err::<(), _>(anyhow!("oops"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(todos.db.saves(), 1);
+
+        todos.flush().unwrap();
+        assert_eq!(todos.db.saves(), 2);
+    }
+
+    #[test]
+    fn flush_logs_a_debug_event_in_its_span() {
+        let logs = testing::Logs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let todos = Todos::new_inmemory();
+            todos.add_message("1").unwrap();
+            todos.flush().unwrap();
+        });
+
+        let logs = logs.contents();
+        let saved: Vec<_> = logs.lines().filter(|line| line.contains("saved")).collect();
+        assert_eq!(saved.len(), 1, "{logs}");
+        assert!(saved[0].contains("DEBUG flush: "), "{logs}");
+        assert!(saved[0].ends_with("saved 1 todos"), "{logs}");
+    }
+
+    #[test]
+    fn flush_listeners_see_every_save() {
+        use std::sync::Arc;
+
+        let todos = Todos::new_inmemory();
+        let seen = Arc::new(Mutex::new(vec![]));
+
+        let sink = seen.clone();
+        todos.on_flush(move |all| {
+            let messages = all.iter().map(|t| t.message.clone()).collect::<Vec<_>>();
+            sink.lock().unwrap().push(messages);
+        });
+
+        todos.add_message("1").unwrap();
+        todos.flush().unwrap();
+        todos
+            .with_deferred_flush(|todos| {
+                todos.add_message("2")?;
+                todos.flush()?;
+                todos.add_message("3")?;
+                todos.flush()?;
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(*seen.lock().unwrap(), vec![vec!["1"], vec!["1", "2", "3"]]);
+    }
+
+    #[test]
+    fn applies_a_todolang_document() {
+        let todos = Todos::new_inmemory();
+        let kept = todos.add_message("b").unwrap();
+        todos.mark_done(&kept.id.0).unwrap();
+        todos.add_message("c").unwrap();
+
+        let src = "todo a\ntodo [x] c\nstray text\ntodo b\ntodo";
+        let applied = todos
+            .apply_todolang_text(lang::parse(src, &config::MyndConfig::default()))
+            .unwrap();
+
+        let ids = |messages: &[&str]| -> Vec<TodoID> {
+            messages.iter().map(|m| TodoID::hash_message(m)).collect()
+        };
+        assert_eq!(applied.ids, ids(&["a", "c", "b"]));
+        assert!(matches!(
+            applied.errors[..],
+            [
+                lang::parser::ParseError::ExtraText(_),
+                lang::parser::ParseError::UnexpectedEof(_)
+            ]
+        ));
+
+        let list = todos.get_all().unwrap();
+        assert_eq!(
+            list.iter().map(|t| t.id.clone()).collect::<Vec<_>>(),
+            ids(&["a", "c", "b"])
+        );
+        assert_eq!(
+            list.iter().map(|t| t.done).collect::<Vec<_>>(),
+            [false, true, true]
+        );
+        assert_eq!(
+            list[2].created_at, kept.created_at,
+            "b is the same todo as before"
+        );
+    }
+
+    #[test]
+    fn finds_by_short_id() {
+        let todos = Todos::new_inmemory();
+        let one = todos.add_message("1").unwrap();
+
+        assert_eq!(one.id.short().len(), TodoID::SHORT_LEN);
+        assert!(one.id.0.starts_with(one.id.short()));
+        assert_eq!(TodoID::from("abc").short(), "abc");
+
+        assert_eq!(todos.get(one.id.short()).unwrap().message, "1");
+        assert!(todos.mark_done(one.id.short()).unwrap());
+
+        todos.add(Todo::new("11".to_string())).unwrap();
+        todos
+            .add(Todo {
+                id: "abcd1234".into(),
+                ..Todo::new("a".to_string())
+            })
+            .unwrap();
+        todos
+            .add(Todo {
+                id: "abcd5678".into(),
+                ..Todo::new("b".to_string())
+            })
+            .unwrap();
+
+        let err = todos.get("abcd").unwrap_err();
+        assert!(format!("{err:#}").contains("more than one"));
+        assert_eq!(todos.get("abcd5").unwrap().message, "b");
+        assert!(todos.get("").is_err());
+    }
+
+    #[test]
+    fn on_day_goes_by_the_timezone_midnight() {
         let todos = Todos::new_inmemory();
+        for (message, time) in [
+            ("before", "2024-06-01T18:59:59Z"),
+            ("midnight", "2024-06-01T19:00:00Z"),
+            ("utc next day", "2024-06-02T03:00:00Z"),
+            ("late", "2024-06-02T18:59:59Z"),
+            ("day after", "2024-06-02T19:00:00Z"),
+        ] {
+            todos.add_with_time(message, time.parse().unwrap()).unwrap();
+        }
+        let on = |date: &str, east_hours: i32| {
+            let tz = chrono::FixedOffset::east_opt(east_hours * 3600).unwrap();
+            todos
+                .on_day_in(date.parse().unwrap(), &tz)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.message)
+                .collect::<Vec<_>>()
+        };
+
+        // 00:00 on the 2nd at UTC+5 is 19:00 on the 1st in UTC
+        assert_eq!(on("2024-06-01", 5), vec!["before"]);
+        assert_eq!(
+            on("2024-06-02", 5),
+            vec!["midnight", "utc next day", "late"]
+        );
+        assert_eq!(on("2024-06-03", 5), vec!["day after"]);
+
+        assert_eq!(
+            on("2024-06-01", -8),
+            vec!["before", "midnight", "utc next day"]
+        );
+        assert_eq!(
+            on("2024-06-02", 0),
+            vec!["utc next day", "late", "day after"]
+        );
+    }
+
+    #[test]
+    fn relative_times_around_the_thresholds() {
+        let now = TodoTime(Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap());
+        let relative = |delta: chrono::Duration| TodoTime(now.0 - delta).relative_to(&now);
+        let seconds = chrono::Duration::seconds;
+        let minutes = chrono::Duration::minutes;
+        let hours = chrono::Duration::hours;
+        let days = chrono::Duration::days;
+
+        assert_eq!(relative(seconds(0)), "just now");
+        assert_eq!(relative(seconds(59)), "just now");
+        assert_eq!(relative(seconds(60)), "1 minute ago");
+        assert_eq!(relative(minutes(59)), "59 minutes ago");
+        assert_eq!(relative(minutes(60)), "1 hour ago");
+        assert_eq!(relative(hours(23)), "23 hours ago");
+        assert_eq!(relative(hours(24)), "yesterday");
+        assert_eq!(relative(hours(47)), "yesterday");
+        assert_eq!(relative(hours(48)), "2 days ago");
+        assert_eq!(relative(days(45)), "1 month ago");
+        assert_eq!(relative(days(800)), "2 years ago");
+
+        assert_eq!(relative(-seconds(30)), "just now");
+        assert_eq!(relative(-hours(3)), "in 3 hours");
+        assert_eq!(relative(-hours(30)), "tomorrow");
+        assert_eq!(relative(-days(3)), "in 3 days");
+    }
+
+    #[test]
+    fn todo_times_sort_totally() {
+        let at = |year, day| TodoTime(Utc.with_ymd_and_hms(year, 6, day, 12, 0, 0).unwrap());
+        let mut times = vec![
+            at(2024, 3),
+            TodoTime::unknown(),
+            at(2200, 1),
+            at(2024, 1),
+            at(2024, 3),
+        ];
+
+        times.sort();
+
+        assert_eq!(
+            times,
+            vec![
+                TodoTime::unknown(),
+                at(2024, 1),
+                at(2024, 3),
+                at(2024, 3),
+                at(2200, 1)
+            ]
+        );
+        assert_eq!(times.iter().max(), Some(&at(2200, 1)));
+    }
 
+    #[test]
+    fn filters_by_creation_time() {
+        let day = |d: u32| TodoTime(Utc.with_ymd_and_hms(2024, 6, d, 12, 0, 0).unwrap());
+        let todos: Vec<_> = (1..=5)
+            .map(|d| Todo {
+                created_at: day(d),
+                ..Todo::new(d.to_string())
+            })
+            .collect();
+
+        let between = |since: Option<TodoTime>, until: Option<TodoTime>| {
+            todos
+                .iter()
+                .filter(|t| t.created_between(since.as_ref(), until.as_ref()))
+                .map(|t| t.message.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(between(Some(day(4)), None), vec!["4", "5"]);
+        assert_eq!(between(None, Some(day(2))), vec!["1", "2"]);
+        assert_eq!(between(Some(day(2)), Some(day(4))), vec!["2", "3", "4"]);
+        assert_eq!(between(None, None).len(), 5);
+    }
+
+    #[test]
+    fn parses_todo_times() {
+        let time: TodoTime = "2024-06-01T12:00:00Z".parse().unwrap();
+        assert_eq!(time.0, Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap());
+
+        let week_ago: TodoTime = "7d".parse().unwrap();
+        let expected = Utc::now() - chrono::Duration::days(7);
+        assert!((week_ago.0 - expected).num_seconds().abs() < 5);
+
+        assert!("2024-06-01".parse::<TodoTime>().is_ok());
+        assert!("last tuesday".parse::<TodoTime>().is_err());
+    }
+
+    #[test]
+    fn reordering_updates_order() {
+        let todos = Todos::new_inmemory();
         todos.add_message("1").unwrap();
         let id = todos.add_message("2").unwrap().id.0;
         todos.add_message("3").unwrap();
-        todos.add_message("4").unwrap();
-        let target = todos.add_message("5").unwrap().id.0;
-        // now, todos = [5, 4, 3, 2, 1]
 
-        todos.move_below(&id, &target).unwrap();
+        todos.move_up(id.to_string()).unwrap();
 
-        let messages = todos
+        let orders = todos
             .get_all()
             .unwrap()
             .into_iter()
+            .map(|t| (t.message, t.order))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            orders,
+            vec![
+                ("2".to_string(), 0),
+                ("1".to_string(), 1),
+                ("3".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn smart_sort_weighs_priority_then_age() {
+        let todos = Todos::new_inmemory();
+        let at = |message: &str, date: &str| todos.add_with_time(message, date.parse().unwrap());
+
+        at("old, low", "2020-01-01").unwrap();
+        at("new, high", "2024-01-01").unwrap();
+        at("new, low", "2024-01-01").unwrap();
+        at("old, high", "2021-01-01").unwrap();
+        let pinned = at("pinned, low", "2024-06-01").unwrap();
+
+        for message in ["new, high", "old, high"] {
+            todos
+                .set_priority(&TodoID::hash_message(message).0, 2)
+                .unwrap();
+        }
+        todos.toggle_pin(&pinned.id.0).unwrap();
+
+        let sorted: Vec<_> = todos
+            .get_all_sorted_smart()
+            .unwrap()
+            .into_iter()
             .map(|t| t.message)
+            .collect();
+
+        assert_eq!(
+            sorted,
+            vec![
+                "pinned, low",
+                "old, high",
+                "new, high",
+                "old, low",
+                "new, low"
+            ]
+        );
+        assert_eq!(
+            messages(&todos),
+            vec![
+                "pinned, low",
+                "old, low",
+                "new, high",
+                "new, low",
+                "old, high"
+            ],
+            "the list itself keeps its manual order"
+        );
+    }
+
+    #[test]
+    fn pinned_todos_lead() {
+        let todos = Todos::new_inmemory();
+        for message in ["1", "2", "3", "4"] {
+            todos.add_message(message).unwrap();
+        }
+
+        assert!(todos.toggle_pin(&TodoID::hash_message("3").0).unwrap());
+        assert!(todos.toggle_pin(&TodoID::hash_message("2").0).unwrap());
+        assert_eq!(messages(&todos), vec!["3", "2", "1", "4"]);
+
+        // reordering can't push them down
+        todos.reorder(&TodoID::hash_message("4").0, 0).unwrap();
+        assert_eq!(messages(&todos), vec!["3", "2", "4", "1"]);
+
+        let stored = todos
+            .get_all()
+            .unwrap()
+            .into_iter()
+            .rev()
+            .map(|t| Todo { order: 0, ..t })
             .collect::<Vec<_>>();
+        let loaded: Vec<_> = TodoArrayList::from(stored)
+            .get_all()
+            .into_iter()
+            .map(|t| t.message)
+            .collect();
+        assert_eq!(loaded, vec!["2", "3", "1", "4"]);
+
+        assert!(!todos.toggle_pin(&TodoID::hash_message("2").0).unwrap());
+        assert_eq!(messages(&todos), vec!["3", "2", "4", "1"]);
+    }
+
+    #[test]
+    fn loading_sorts_by_order() {
+        let stored = [("a", 2), ("b", 0), ("c", 1)]
+            .map(|(message, order)| Todo {
+                order,
+                ..Todo::new(message.to_string())
+            })
+            .to_vec();
+
+        let messages: Vec<_> = TodoArrayList::from(stored)
+            .get_all()
+            .into_iter()
+            .map(|t| t.message)
+            .collect();
+        assert_eq!(messages, vec!["b", "c", "a"]);
+
+        // files from before `order` existed have every order at 0
+        let legacy = ["a", "b", "c"].map(|m| Todo::new(m.to_string())).to_vec();
+        let orders: Vec<_> = TodoArrayList::from(legacy)
+            .get_all()
+            .into_iter()
+            .map(|t| (t.message, t.order))
+            .collect();
+        assert_eq!(
+            orders,
+            vec![
+                ("a".to_string(), 0),
+                ("b".to_string(), 1),
+                ("c".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn move_below_from_top_to_bottom() {
+        let todos = Todos::new_inmemory();
+
+        todos.add_message("1").unwrap();
+        todos.add_message("2").unwrap();
+        let target = todos.add_message("3").unwrap().id.0;
+        todos.add_message("4").unwrap();
+        let id = todos.add_message("5").unwrap().id.0;
+        // now, todos = [5, 4, 3, 2, 1]
+
+        todos.move_below(&id, &target).unwrap();
+
+        assert_eq!(
+            messages(&todos),
+            vec![
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string(),
+                "5".to_string(),
+                "4".to_string(),
+            ]
+        )
+    }
+
+    #[test]
+    fn move_below_from_bottom_to_top() {
+        let todos = Todos::new_inmemory();
+
+        todos.add_message("1").unwrap();
+        let id = todos.add_message("2").unwrap().id.0;
+        todos.add_message("3").unwrap();
+        todos.add_message("4").unwrap();
+        let target = todos.add_message("5").unwrap().id.0;
+        // now, todos = [5, 4, 3, 2, 1]
+
+        todos.move_below(&id, &target).unwrap();
 
         assert_eq!(
-            messages,
+            messages(&todos),
             vec![
                 "1".to_string(),
                 "3".to_string(),
@@ -288,6 +2317,71 @@ mod tests {
         )
     }
 
+    #[test]
+    fn moves_in_a_one_todo_list_are_noops() {
+        let todos = Todos::new_inmemory();
+        let only = todos.add_message("only").unwrap();
+        let id = only.id.0.to_string();
+
+        todos.move_up(id.clone()).unwrap();
+        todos.move_down(id.clone()).unwrap();
+        todos.move_below(&id, &id).unwrap();
+        todos.reorder(&id, 0).unwrap();
+        todos.reorder(&id, 3).unwrap();
+        todos.move_many_to_top(&[&id]).unwrap();
+        todos.move_many_to_bottom(&[&id]).unwrap();
+
+        let all = todos.get_all().unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!((all[0].id.clone(), all[0].order), (only.id, 0));
+    }
+
+    #[test]
+    fn moving_past_the_ends_is_a_noop() {
+        let todos = Todos::new_inmemory();
+        let top = todos.add_message("1").unwrap().id.0.to_string();
+        let bottom = todos.add_message("2").unwrap().id.0.to_string();
+        todos.move_up(top.clone()).unwrap();
+        todos.move_down(bottom.clone()).unwrap();
+        todos.move_below(&bottom, &top).unwrap();
+        assert_eq!(messages(&todos), vec!["1", "2"]);
+
+        todos.move_down(top).unwrap();
+        assert_eq!(messages(&todos), vec!["2", "1"]);
+    }
+
+    #[test]
+    fn move_below_says_which_id_is_missing() {
+        let todos = Todos::new_inmemory();
+        let one = todos.add_message("1").unwrap().id.0;
+        todos.add_message("2").unwrap();
+        let before = todos.get_all().unwrap();
+        let unchanged = || {
+            let after = todos.get_all().unwrap();
+            assert_eq!(after.len(), before.len());
+            for (a, b) in after.iter().zip(&before) {
+                assert_eq!((&a.id, &a.updated_at), (&b.id, &b.updated_at));
+            }
+        };
+
+        let err = todos.move_below("nope", &one).unwrap_err();
+        assert!(format!("{err:#}").contains("todo to move: nope"), "{err:#}");
+        unchanged();
+
+        let err = todos.move_below(&one, "nada").unwrap_err();
+        assert!(
+            format!("{err:#}").contains("to move below: nada"),
+            "{err:#}"
+        );
+        unchanged();
+
+        let err = todos.move_below("nope", "nada").unwrap_err();
+        assert!(format!("{err:#}").contains("neither"), "{err:#}");
+        assert!(format!("{err:#}").contains("nope"), "{err:#}");
+        assert!(format!("{err:#}").contains("nada"), "{err:#}");
+        unchanged();
+    }
+
     #[test]
     fn move_below_to_bottom() {
         let todos = Todos::new_inmemory();
@@ -301,15 +2395,8 @@ mod tests {
 
         todos.move_below(&id, &target).unwrap();
 
-        let messages = todos
-            .get_all()
-            .unwrap()
-            .into_iter()
-            .map(|t| t.message)
-            .collect::<Vec<_>>();
-
         assert_eq!(
-            messages,
+            messages(&todos),
             vec![
                 "1".to_string(),
                 "5".to_string(),
@@ -1,383 +1,3841 @@
-use std::os::unix::process::CommandExt;
+use std::{os::unix::process::CommandExt, path::PathBuf};
 
 use anyhow::Context;
 use clap::{Parser, Subcommand};
-use todo::Todos;
+use todo::{config, lang, Todos};
 
-mod config;
-mod lang;
 mod lang_server;
+#[cfg(feature = "serve")]
+mod serve;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Cli {
-    /// What to do.
+    /// What to do; `-` to read it from stdin.
     message: Option<String>,
 
+    /// Read and write the config at this path, instead of the default location.
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Use the todos of the project we're in, kept in the nearest .mynd directory up from here.
+    #[arg(long, global = true)]
+    local: bool,
+
+    /// Print how long loading, changing and saving the todos took.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Never ask for confirmation; what would have asked fails instead, unless given --yes.
+    #[arg(long, global = true)]
+    no_input: bool,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
+    /// Add a todo item, optionally as if it was created at another time.
+    Add(add::AddArgs),
+
     /// Mark one or more todo items as done.
-    Done {
-        /// Ids of the todo(s) to mark done.
-        ids: Vec<String>,
-    },
+    Done(done::DoneArgs),
     /// Delete a todo item, regardless of if it's done or not.
     Rm(remove::RemoveArgs),
 
+    /// Delete all the todos that are done, for good; there's no archive to move them to.
+    Clean(clean::CleanArgs),
+
+    /// Pin (or unpin) todo items, to keep them at the top.
+    Pin(pin::PinArgs),
+
+    /// Set how urgent every todo whose message contains some text is.
+    Prioritize(prioritize::PrioritizeArgs),
+
     /// List all todos that aren't done.
     Ls(ls::LsArgs),
 
+    /// Show the full details of one todo item.
+    Show(show::ShowArgs),
+
     /// Launch the GUI (mynd). Assuming it's in the path.
     Gui,
 
     /// Read and save todos from a given file
     Import(import::ImportArgs),
 
+    /// Reconcile the todos with another save file, keeping the newer copy of todos in both.
+    Merge(merge::MergeArgs),
+
     /// Edit the todo list in your default editor ($EDITOR) [default]
     Edit(edit::Edit),
 
+    /// Print the path of the save file.
+    Where,
+
+    /// Put back the save file from before the last change; run again to undo that.
+    Revert,
+
+    /// Print the changes in the save file's journal, oldest first, to see what happened.
+    Log,
+
+    /// Open the directory of the save file in the file manager.
+    Open,
+
     /// Dump all todos as json.
     Dump(dump::DumpArgs),
 
+    /// Print the pending todos as one string to paste into `import --from-share` elsewhere.
+    Share(share::ShareArgs),
+
+    /// Save a timestamped copy of the todos, to `diff` against later.
+    Snapshot,
+
+    /// Show the todos added, removed and completed from one snapshot (or export) to another.
+    Diff(snapshot::DiffArgs),
+
+    /// Tidy the save-file after editing it by hand: drop duplicate ids and renumber the list.
+    Normalize,
+
+    /// Give todos whose message was edited outside mynd the id that goes with it now.
+    Reindex,
+
+    /// Salvage what todos can be read from a corrupt binary save-file, into a new file.
+    Recover(recover::RecoverArgs),
+
+    /// Export todos for other apps.
+    Export(export::ExportArgs),
+
     /// Manage global configuration values.
     Config(manageconfigcli::ConfigArgs),
 
     /// Start the language server.
     Lsp,
+
+    /// Print JSON Schemas of the todo types, for generating frontend types.
+    Schema,
+
+    /// Report the errors in a todo language file, without taking in its todos.
+    Check(check::CheckArgs),
+
+    /// Print the tokens of a todo language file as json, to see how it's being read.
+    #[command(hide = true)]
+    Lex(lex::LexArgs),
+
+    /// Serve the todos over a local HTTP JSON API.
+    #[cfg(feature = "serve")]
+    Serve(serve::ServeArgs),
+}
+
+impl Cli {
+    /// How to ask for confirmation, going by `--no-input`.
+    fn prompt(&self) -> prompt::Prompt {
+        prompt::Prompt::new(self.no_input)
+    }
+}
+
+impl Command {
+    /// Whether this is meant to change the todos, as opposed to just looking at them.
+    fn changes_todos(&self) -> bool {
+        matches!(
+            self,
+            Command::Add(_)
+                | Command::Done(_)
+                | Command::Rm(_)
+                | Command::Pin(_)
+                | Command::Prioritize(_)
+                | Command::Clean(_)
+                | Command::Import(_)
+                | Command::Merge(_)
+                | Command::Edit(_)
+                | Command::Normalize
+                | Command::Reindex
+        )
+    }
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
 
+    init_logging(args.verbose);
+
+    let prompt = args.prompt();
+
+    if let Some(path) = args.config.clone() {
+        config::use_config_path(path);
+    }
+
+    if args.local {
+        let cwd = std::env::current_dir().context("failed to get the current directory")?;
+        match todo::persist::find_local_save_file(&cwd) {
+            Some(path) => todo::persist::use_save_file(path),
+            None => eprintln!("[WARN] no .mynd directory here or above, using the global todos"),
+        }
+    }
+
     let todos = Todos::load_up_with_persistor();
+    let changes_todos = args.command.as_ref().is_none_or(Command::changes_todos);
 
     match args.command {
         Some(c) => match c {
-            Command::Done { ids } => {
-                for id in ids {
-                    todos.mark_done(&id)?;
-                    eprintln!("[INFO] marked done todo id: {}", id);
-                    todos.flush()?;
-                }
-            }
+            Command::Add(a) => a.handle()?,
+            Command::Done(a) => a.handle(prompt)?,
             Command::Ls(a) => a.handle()?,
+            Command::Show(a) => a.handle()?,
             Command::Dump(a) => a.handle()?,
+            Command::Share(a) => a.handle()?,
+            Command::Snapshot => {
+                savefile::ensure_saving("snapshot")?;
+                let path = snapshot::take(&todos)?;
+                eprintln!("[INFO] saved a snapshot of the todos");
+                println!("{}", path.display());
+            }
+            Command::Diff(a) => a.handle()?,
+            Command::Where => println!("{}", savefile::path()?.display()),
+            Command::Open => savefile::open_dir()?,
+            Command::Revert => {
+                savefile::ensure_saving("revert")?;
+                todo::persist::revert(&savefile::path()?)?;
+                eprintln!("[INFO] reverted the todos to before the last change");
+            }
+            Command::Log => {
+                let entries = todos.db.journal_entries()?;
+                if entries.is_empty() {
+                    eprintln!("[INFO] the journal is empty; it only has what changed since it was last compacted, when `journal` is on");
+                }
+                for entry in entries {
+                    println!("{} {}", entry.at.to_local_date_string(), entry.change);
+                }
+            }
+            Command::Recover(a) => a.handle()?,
+            Command::Export(a) => a.handle()?,
             Command::Import(a) => a.handle()?,
+            Command::Merge(a) => a.handle()?,
             Command::Config(a) => a.handle()?,
-            Command::Rm(a) => a.handle()?,
+            Command::Rm(a) => a.handle(prompt)?,
+            Command::Pin(a) => a.handle()?,
+            Command::Prioritize(a) => a.handle(prompt)?,
+            Command::Clean(a) => a.handle(prompt)?,
+            Command::Reindex => {
+                let reindexed = todos.reindex()?;
+                eprintln!("[INFO] changed {} id(s)", reindexed.changed);
+                if reindexed.dropped > 0 {
+                    eprintln!(
+                        "[INFO] removed {} todo(s) that ended up the same as older ones",
+                        reindexed.dropped
+                    );
+                }
+            }
+            Command::Normalize => {
+                let removed = todos.normalize()?;
+                eprintln!("[INFO] removed {} duplicate todo(s)", removed);
+            }
             Command::Gui => {
                 let err = std::process::Command::new("mynd").exec();
                 return Err(err).context("failed to run the executable `mynd`. See the README @ https://github.com/Gnarus-G/mynd");
             }
             Command::Lsp => lang_server::start(),
             Command::Edit(a) => a.handle()?,
+            Command::Lex(a) => a.handle()?,
+            Command::Check(a) => a.handle()?,
+            Command::Schema => {
+                serde_json::to_writer_pretty(std::io::stdout(), &todo::json_schemas())?;
+                println!()
+            }
+            #[cfg(feature = "serve")]
+            Command::Serve(a) => a.handle()?,
         },
         None => match args.message {
             Some(message) => {
+                savefile::ensure_writable()?;
+                let message = add::message_from(message, std::io::stdin())?;
                 todos.add_message(&message)?;
                 todos.flush()?;
             }
-            None => edit::Edit.handle()?,
+            None => edit::Edit::default().handle()?,
         },
     }
 
+    if changes_todos {
+        let cfg = config::load_config_or_default();
+        prune::done_if_configured(&cfg, &todos)?;
+    }
+
     Ok(())
 }
 
-mod ls {
-    use clap::Args;
-    use colored::Colorize;
-    use todo::Todos;
+/// Log to stderr, warnings only unless e.g. `RUST_LOG=mynd=debug` asks for more. Spans log when
+/// they close, with how long they took.
+fn init_logging(verbose: bool) {
+    use tracing_subscriber::fmt::format::FmtSpan;
 
-    #[derive(Debug, Args)]
-    pub struct LsArgs {
-        /// Show todos that are done as well.
-        #[arg(short, long)]
-        pub full: bool,
+    tracing_subscriber::fmt()
+        .with_env_filter(log_filter(verbose))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+}
 
-        /// Show only the todo messages.
-        #[arg(short, long)]
-        pub quiet: bool,
+/// Warnings, from `RUST_LOG` otherwise. Being `verbose` adds the timed spans of the todos'
+/// loading, changes and saves, which are logged at debug.
+fn log_filter(verbose: bool) -> tracing_subscriber::EnvFilter {
+    use tracing_subscriber::{filter::LevelFilter, EnvFilter};
+
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::WARN.into())
+        .parse_lossy(log_directives(
+            &std::env::var("RUST_LOG").unwrap_or_default(),
+        ));
+
+    if verbose {
+        filter.add_directive("todo=debug".parse().expect("a valid directive"))
+    } else {
+        filter
     }
+}
 
-    impl LsArgs {
-        pub fn handle(self) -> anyhow::Result<()> {
-            let todos = Todos::load_up_with_persistor();
-
-            todos
-                .get_all()?
-                .into_iter()
-                .filter(|t| self.full || !t.done)
-                .for_each(|t| {
-                    if !self.quiet {
-                        eprintln!("{}      {}", "id:".dimmed(), t.id.0.dimmed());
-                        eprintln!(
-                            "{}    {}",
-                            "time:".dimmed(),
-                            t.created_at.to_local_date_string().dimmed()
-                        );
-                    }
+/// `RUST_LOG` with `mynd`, what the app goes by, standing for the crate's own target, `todo`.
+fn log_directives(rust_log: &str) -> String {
+    rust_log
+        .split(',')
+        .map(|directive| match directive.strip_prefix("mynd") {
+            Some(rest) if rest.is_empty() || rest.starts_with(['=', ':', '[']) => {
+                format!("todo{rest}")
+            }
+            _ => directive.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
 
-                    let message = if t.done {
-                        t.message.strikethrough().dimmed()
-                    } else {
-                        t.message.yellow()
-                    };
+#[cfg(test)]
+mod tests {
+    use todo::{testing::Logs, Todos};
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    fn logged_flush(verbose: bool) -> String {
+        let logs = Logs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(super::log_filter(verbose))
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let todos = Todos::new_inmemory();
+            todos.add_message("time me").unwrap();
+            todos.flush().unwrap();
+        });
+
+        logs.contents()
+    }
 
-                    if !self.quiet {
-                        println!(
-                            "{} {}{}{}",
-                            "message:".dimmed(),
-                            "\"".dimmed(),
-                            message,
-                            "\"".dimmed()
-                        );
-                    } else {
-                        println!("{}", message);
-                    }
+    #[test]
+    fn mynd_stands_for_the_crate_in_rust_log() {
+        assert_eq!(
+            super::log_directives("mynd=debug,hyper=warn"),
+            "todo=debug,hyper=warn"
+        );
+        assert_eq!(
+            super::log_directives("mynd::persist=trace,myndless=info"),
+            "todo::persist=trace,myndless=info"
+        );
+        assert_eq!(super::log_directives("info"), "info");
+    }
 
-                    if !self.quiet {
-                        eprintln!()
-                    }
-                });
+    #[test]
+    fn verbose_times_the_flush() {
+        let logs = logged_flush(true);
+        assert!(
+            logs.lines()
+                .any(|line| line.contains("flush") && line.contains("time.busy")),
+            "{logs}"
+        );
 
-            Ok(())
-        }
+        assert_eq!(logged_flush(false), "");
     }
 }
 
-mod edit {
-    use std::{
-        fs::File,
-        io::{BufWriter, Write},
-    };
+mod savefile {
+    use std::path::{Path, PathBuf};
 
     use anyhow::{anyhow, Context};
-    use clap::Args;
-    use todo::Todos;
+    use todo::persist;
 
-    #[derive(Debug, Args)]
-    pub struct Edit;
+    use crate::config;
 
-    impl Edit {
-        pub fn handle(self) -> anyhow::Result<()> {
-            let todos = Todos::load_up_with_persistor();
+    /// The save file for the configured format.
+    pub fn path() -> anyhow::Result<PathBuf> {
+        let cfg = config::load_config_or_default();
 
-            let temp_filename = "/tmp/mynd-todo.td";
-            let mut file = File::options()
-                .read(true)
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(temp_filename)
-                .map(BufWriter::new)?;
+        Ok(persist::save_file_path(&cfg.save_file_format)?)
+    }
 
-            for todo in todos.get_all()? {
-                write!(file, "todo ")?;
+    /// Fail early if the save file's directory can't be written to, rather than after the work
+    /// of an edit that then can't be saved.
+    pub fn ensure_writable() -> anyhow::Result<()> {
+        if persist::in_memory() {
+            return Ok(());
+        }
 
-                if todo.message.lines().count() > 1 {
-                    writeln!(file, "{{")?;
-                    for line in todo.message.lines() {
-                        writeln!(file, "  {}", line)?;
-                    }
-                    writeln!(file, "}}")?;
-                } else {
-                    writeln!(file, "{}", todo.message)?;
-                }
+        let path = path()?;
+        let dir = path.parent().context("the save file has no directory")?;
+        ensure_dir_writable(dir)
+    }
 
-                writeln!(file)?;
-            }
+    /// A directory marked read-only counts as unwritable, even for users who could write to it
+    /// anyway, like root.
+    fn ensure_dir_writable(dir: &Path) -> anyhow::Result<()> {
+        let readonly = std::fs::metadata(dir)
+            .with_context(|| format!("failed to find the save directory {}", dir.display()))?
+            .permissions()
+            .readonly();
+
+        let probe = dir.join(".mynd-write-check");
+        let writable = !readonly
+            && std::fs::File::create(&probe)
+                .and_then(|_| std::fs::remove_file(&probe))
+                .is_ok();
+
+        if !writable {
+            return Err(anyhow!(
+                "can't write to {}, where the todos are saved; check its permissions",
+                dir.display()
+            ));
+        }
 
-            drop(file);
+        Ok(())
+    }
 
-            let editor =
-                std::env::var("EDITOR").context("failed to get the user's default editor")?;
+    /// Fail for a command that works on files of its own rather than the todos, when nothing is
+    /// to be saved because of $MYND_INMEMORY.
+    pub fn ensure_saving(command: &str) -> anyhow::Result<()> {
+        if persist::in_memory() {
+            return Err(anyhow!(
+                "`{command}` works on the files the todos are saved in, which aren't used with {}=1",
+                persist::IN_MEMORY_VAR
+            ));
+        }
 
-            let exitstatus = std::process::Command::new(&editor)
-                .arg(temp_filename)
-                .spawn()
-                .context(anyhow!("failed to open editor: {}", editor))?
-                .wait()?;
+        Ok(())
+    }
 
-            eprintln!("[INFO] {}", exitstatus);
+    pub fn open_dir() -> anyhow::Result<()> {
+        let path = path()?;
+        let dir = path.parent().context("the save file has no directory")?;
+        let opener = if cfg!(target_os = "macos") {
+            "open"
+        } else {
+            "xdg-open"
+        };
+
+        let status = std::process::Command::new(opener)
+            .arg(dir)
+            .status()
+            .with_context(|| format!("failed to run `{opener}`"))?;
+
+        if !status.success() {
+            return Err(anyhow!("`{opener}` failed to open {}", dir.display()));
+        }
 
-            Ok(())
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+        use super::ensure_dir_writable;
+
+        #[test]
+        fn read_only_save_dir_is_caught() {
+            let dir = tempfile::tempdir().unwrap();
+            ensure_dir_writable(dir.path()).unwrap();
+            assert!(!dir.path().join(".mynd-write-check").exists());
+
+            std::fs::set_permissions(dir.path(), Permissions::from_mode(0o555)).unwrap();
+            let err = ensure_dir_writable(dir.path()).unwrap_err();
+            assert!(err.to_string().contains("can't write to"), "{err:#}");
+
+            std::fs::set_permissions(dir.path(), Permissions::from_mode(0o755)).unwrap();
         }
     }
 }
 
-mod remove {
-    use clap::Args;
-    use todo::Todos;
+mod prompt {
+    use std::io::{stderr, stdin, Write};
 
-    #[derive(Args, Debug)]
-    pub struct RemoveArgs {
-        /// Id(s) of the todo(s) to delete.
-        ids: Vec<String>,
+    use anyhow::anyhow;
+
+    /// Asks for confirmation on the terminal, unless told there's no one there to answer.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Prompt {
+        /// Fail instead of asking, for scripts that can't answer.
+        no_input: bool,
     }
 
-    impl RemoveArgs {
-        pub fn handle(self) -> anyhow::Result<()> {
-            let todos = Todos::load_up_with_persistor();
+    impl Prompt {
+        pub fn new(no_input: bool) -> Self {
+            Self { no_input }
+        }
 
-            for id in self.ids {
-                match todos.remove(&id) {
-                    Ok(_) => {
-                        eprintln!("[INFO] deleted todo id: {}", id)
-                    }
-                    Err(err) => {
-                        eprintln!("[ERROR] failed to remove todo id: {}", id);
-                        eprintln!("[ERROR] {err:#}")
-                    }
-                }
+        /// Ask a yes/no question on the terminal, defaulting to no.
+        pub fn confirm(self, question: &str) -> anyhow::Result<bool> {
+            if self.no_input {
+                return Err(anyhow!(
+                    "this needs confirmation ({question}) but --no-input was given; pass --yes to go ahead"
+                ));
             }
 
-            todos.flush()?;
+            eprint!("{question} [y/N] ");
+            stderr().flush()?;
 
-            Ok(())
+            let mut answer = String::new();
+            stdin().read_line(&mut answer)?;
+
+            Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+        }
+
+        /// Whether to go ahead deleting `count` todos: asks when that's more than `threshold`,
+        /// unless told `yes` already.
+        pub fn confirm_delete(
+            self,
+            count: usize,
+            threshold: usize,
+            yes: bool,
+        ) -> anyhow::Result<bool> {
+            if yes || count <= threshold {
+                return Ok(true);
+            }
+
+            self.confirm(&format!("delete {count} todo(s)?"))
         }
     }
-}
 
-mod import {
-    use std::{ffi::OsStr, path::PathBuf};
+    #[cfg(test)]
+    mod tests {
+        use super::Prompt;
 
-    use anyhow::{anyhow, Context};
-    use todo::persist::{binary, jsonfile, ActualTodosDB, TodosDatabase};
+        #[test]
+        fn asks_to_delete_over_the_threshold() {
+            let prompt = Prompt::new(true);
+
+            assert!(prompt.confirm_delete(5, 5, false).unwrap());
+            assert!(prompt.confirm_delete(6, 5, true).unwrap());
+
+            let err = prompt.confirm_delete(6, 5, false).unwrap_err();
+            assert!(err.to_string().contains("delete 6 todo(s)?"), "{err:#}");
+        }
+    }
+}
 
+mod pick {
     use clap::Args;
+    use todo::{persist::TodosDatabase, Todos};
 
+    /// Select a todo by how old it is, instead of by id.
     #[derive(Debug, Args)]
-    pub struct ImportArgs {
-        /// from which to read todo items
-        file: PathBuf,
+    pub struct Extreme {
+        /// Act on the oldest todo that isn't done.
+        #[arg(long, conflicts_with = "ids")]
+        oldest: bool,
+
+        /// Act on the newest todo that isn't done.
+        #[arg(long, conflicts_with_all = ["ids", "oldest"])]
+        newest: bool,
     }
 
-    impl ImportArgs {
-        pub fn handle(self) -> anyhow::Result<()> {
-            let file = self.file;
+    impl Extreme {
+        /// The id of the selected todo, if a selection was asked for and anything pending.
+        pub fn resolve<DB: TodosDatabase>(
+            &self,
+            todos: &Todos<DB>,
+        ) -> anyhow::Result<Option<String>> {
+            let todo = if self.oldest {
+                todos.oldest()?
+            } else if self.newest {
+                todos.newest()?
+            } else {
+                return Ok(None);
+            };
 
-            let supported_extensions = &["json", "bin"].map(OsStr::new);
+            if todo.is_none() {
+                eprintln!("[INFO] no pending todos to pick from");
+            }
 
-            let ext = file
-                .extension()
-                .filter(|ext| supported_extensions.contains(ext))
-                .context(anyhow!(
-                    "extension is not one of the only supported: {:?}",
-                    supported_extensions.map(|s| s.to_string_lossy()),
-                ))
-                .and_then(|e| e.to_str().context("file extension is not in utf-8"));
+            Ok(todo.map(|t| t.id.0.to_string()))
+        }
 
-            let db = ActualTodosDB::default();
+        pub fn is_set(&self) -> bool {
+            self.oldest || self.newest
+        }
+    }
+}
 
-            let imported_todos;
+mod prune {
+    use todo::{persist::TodosDatabase, Todos};
 
-            match ext {
-                    Ok("json") => {
-                        imported_todos = jsonfile::read_json(&file)?;
-                    }
-                    Ok("bin") => {
-                        let mut data =
-                            std::fs::read(file).context("failed to read from import file")?;
-                        imported_todos = binary::get_todos_from_binary(&mut data)?;
-                    }
-                    Err(err) => {
-                        return Err(err.context("unsupported file extension"))
-                    }
-                    _ => unreachable!("unreachable assertion failed even though we are[should be] filter out unsupported extensions in an error"),
-                }
+    use crate::config::MyndConfig;
+
+    /// Remove done todos, if `auto_prune_done` is on. Returns how many were removed.
+    /// There's no archive yet, so they're deleted outright rather than archived.
+    pub fn done_if_configured<DB: TodosDatabase>(
+        cfg: &MyndConfig,
+        todos: &Todos<DB>,
+    ) -> anyhow::Result<usize> {
+        if !cfg.auto_prune_done {
+            return Ok(0);
+        }
+
+        // the command may well have saved changes through its own handle on the todos
+        todos.reload()?;
+        let done = todos.with_iter(|all| all.filter(|t| t.done).count())?;
+        if done > 0 {
+            todos.remove_done()?;
+            eprintln!("[INFO] pruned {} done todo(s)", done);
+        }
 
-            let mut todos = db
-                .get_all_todos()
-                .context("failed to load current set of todos")?;
+        Ok(done)
+    }
 
-            todos.extend(imported_todos);
+    #[cfg(test)]
+    mod tests {
+        use todo::{
+            testing::{messages, MemoryDB},
+            Todo, Todos,
+        };
+
+        use super::*;
+
+        #[test]
+        fn prunes_done_todos_only_when_configured() {
+            let todos = Todos::new(MemoryDB::with(vec![
+                Todo::new("1".to_string()),
+                Todo {
+                    done: true,
+                    ..Todo::new("2".to_string())
+                },
+            ]));
+            let saved = || {
+                todos.reload().unwrap();
+                messages(&todos)
+            };
 
-            db.set_all_todos(todos)?;
+            let mut cfg = MyndConfig::default();
+            assert_eq!(done_if_configured(&cfg, &todos).unwrap(), 0);
+            assert_eq!(saved(), vec!["1", "2"]);
 
-            Ok(())
+            cfg.auto_prune_done = true;
+            assert_eq!(done_if_configured(&cfg, &todos).unwrap(), 1);
+            assert_eq!(saved(), vec!["1"]);
         }
     }
 }
 
-mod dump {
+mod add {
+    use std::io::Read;
+
+    use anyhow::Context;
     use clap::Args;
-    use todo::Todos;
+    use todo::{TodoTime, Todos};
 
-    #[derive(Debug, Args)]
-    pub struct DumpArgs {
-        /// Only dump undone todo items
-        #[arg(short = 't')]
-        todo: bool,
+    /// The message as given, or read whole from `input` if it's `-`, less the trailing newline.
+    pub fn message_from(message: String, mut input: impl Read) -> anyhow::Result<String> {
+        if message != "-" {
+            return Ok(message);
+        }
+
+        let mut message = String::new();
+        input
+            .read_to_string(&mut message)
+            .context("failed to read the todo message from stdin")?;
+
+        Ok(message.trim_end_matches(['\r', '\n']).to_string())
     }
 
-    impl DumpArgs {
+    #[derive(Args, Debug)]
+    pub struct AddArgs {
+        /// What to do; `-` to read it from stdin.
+        message: String,
+
+        /// When the todo was created (RFC 3339, YYYY-MM-DD, or ago e.g. 7d) [default: now]
+        #[arg(long)]
+        at: Option<TodoTime>,
+
+        /// How urgent the todo is; higher is more urgent. [default: 0]
+        #[arg(short, long)]
+        priority: Option<u8>,
+    }
+
+    impl AddArgs {
         pub fn handle(self) -> anyhow::Result<()> {
+            crate::savefile::ensure_writable()?;
             let todos = Todos::load_up_with_persistor();
+            let message = message_from(self.message, std::io::stdin())?;
 
-            let todos: Vec<_> = todos
-                .get_all()?
-                .into_iter()
-                .filter(|t| !self.todo || !t.done)
-                .collect();
-
-            println!("{}", serde_json::to_string(&todos)?);
+            let todo = match self.at {
+                Some(at) => todos.add_with_time(&message, at)?,
+                None => todos.add_message(&message)?,
+            };
+            if let Some(priority) = self.priority {
+                todos.set_priority(&todo.id.0, priority)?;
+            }
+            todos.flush()?;
 
             Ok(())
         }
     }
-}
 
-mod manageconfigcli {
-    use std::io::stdout;
+    #[cfg(test)]
+    mod tests {
+        use todo::Todos;
 
-    use clap::{Args, Subcommand};
+        use super::message_from;
 
-    use crate::config::{self, store_config};
+        #[test]
+        fn reads_a_dash_message_from_stdin() {
+            let stdin = "long\nmultiline\n\nnote\n".as_bytes();
+            let message = message_from("-".to_string(), stdin).unwrap();
 
-    #[derive(Args, Debug)]
-    pub struct ConfigProps {
-        #[arg(short = 'f', long = "format")]
-        /// The storage format of the collection of todo items.
-        storage_format: config::SaveFileFormat,
-    }
+            let todos = Todos::new_inmemory();
+            let todo = todos.add_message(&message).unwrap();
+            assert_eq!(
+                todos.get(&todo.id.0).unwrap().message,
+                "long\nmultiline\n\nnote"
+            );
 
-    #[derive(Subcommand, Debug)]
-    pub enum ConfigActions {
-        /// Update configuration values.
-        Set(ConfigProps),
-        /// Print configuration values to standard output as json.
-        Show,
+            let untouched = message_from("a - b".to_string(), "ignored".as_bytes()).unwrap();
+            assert_eq!(untouched, "a - b");
+        }
     }
+}
 
-    #[derive(Args, Debug)]
-    pub struct ConfigArgs {
-        #[command(subcommand)]
-        command: ConfigActions,
+mod done {
+    use clap::Args;
+    use todo::{persist::TodosDatabase, Todos};
+
+    use crate::{config, pick, prompt::Prompt};
+
+    #[derive(Debug, Args)]
+    pub struct DoneArgs {
+        /// Ids of the todo(s) to mark done.
+        ids: Vec<String>,
+
+        #[command(flatten)]
+        pick: pick::Extreme,
+
+        /// Mark done all todos whose message contains this text.
+        #[arg(short, long, conflicts_with_all = ["ids", "oldest", "newest"])]
+        matching: Option<String>,
+
+        /// Don't ask for confirmation.
+        #[arg(short, long)]
+        yes: bool,
     }
 
-    impl ConfigArgs {
-        pub fn handle(self) -> anyhow::Result<()> {
-            match self.command {
-                ConfigActions::Set(ConfigProps { storage_format }) => {
-                    let cfg = config::MyndConfig {
-                        save_file_format: storage_format,
-                    };
+    impl DoneArgs {
+        pub fn handle(self, prompt: Prompt) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
 
-                    store_config(cfg)?;
+            if let Some(query) = self.matching {
+                let pending: Vec<_> = todos
+                    .search(&query)?
+                    .into_iter()
+                    .filter(|t| !t.done)
+                    .collect();
+
+                if pending.is_empty() {
+                    eprintln!("[INFO] no pending todos match: {:?}", query);
+                    return Ok(());
                 }
-                ConfigActions::Show => {
-                    let cfg = config::load_config()?;
-                    serde_json::to_writer_pretty(stdout(), &cfg)?;
-                    println!()
+
+                for todo in &pending {
+                    eprintln!("  {}", todo.message);
                 }
-            };
 
-            Ok(())
+                let question = format!("mark {} todo(s) done?", pending.len());
+                if !self.yes && !prompt.confirm(&question)? {
+                    eprintln!("[INFO] aborted");
+                    return Ok(());
+                }
+
+                let count = todos.mark_done_matching(&query)?;
+                eprintln!("[INFO] marked done {} todo(s)", count);
+                todos.flush()?;
+
+                return Ok(());
+            }
+
+            let ids = if self.pick.is_set() {
+                self.pick.resolve(&todos)?.into_iter().collect()
+            } else {
+                self.ids
+            };
+
+            let cfg = config::load_config_or_default();
+
+            mark_done_ids(&todos, &ids, cfg.done_toggles)?;
+
+            Ok(())
+        }
+    }
+
+    /// What `done` did to a todo.
+    #[derive(Debug, PartialEq)]
+    enum Marked {
+        Done,
+        /// Toggled back, for being done already.
+        NotDone,
+        AlreadyDone,
+    }
+
+    impl std::fmt::Display for Marked {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Marked::Done => write!(f, "marked done"),
+                Marked::NotDone => write!(f, "marked not done"),
+                Marked::AlreadyDone => write!(f, "already done"),
+            }
+        }
+    }
+
+    /// Mark the todos done, or with `toggles`, undo the ones that already are.
+    /// Returns what was done to each.
+    fn mark_done_ids<DB: TodosDatabase>(
+        todos: &Todos<DB>,
+        ids: &[String],
+        toggles: bool,
+    ) -> anyhow::Result<Vec<Marked>> {
+        todos.with_deferred_flush(|todos| {
+            let mut marked = vec![];
+            for id in ids {
+                let changed = if toggles {
+                    todos.mark_done(id)?
+                } else {
+                    todos.set_done(id, true)?
+                };
+
+                let outcome = match (changed, todos.get(id)?.done) {
+                    (true, true) => Marked::Done,
+                    (true, false) => Marked::NotDone,
+                    (false, _) => Marked::AlreadyDone,
+                };
+                eprintln!("[INFO] {} todo id: {}", outcome, id);
+                marked.push(outcome);
+
+                todos.flush()?;
+            }
+            Ok(marked)
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::{testing::CountingDB, Todos};
+
+        #[test]
+        fn done_many_saves_once() {
+            let todos = Todos::new(CountingDB::default());
+            let ids: Vec<_> = ["1", "2", "3"]
+                .map(|m| todos.add_message(m).unwrap().id.0.to_string())
+                .to_vec();
+
+            super::mark_done_ids(&todos, &ids, true).unwrap();
+
+            assert_eq!(todos.db.saves(), 1);
+            assert!(todos.get_all().unwrap().iter().all(|t| t.done));
+        }
+
+        #[test]
+        fn done_again_toggles_it_off() {
+            let todos = Todos::new(CountingDB::default());
+            let ids = [todos.add_message("1").unwrap().id.0.to_string()];
+
+            let marked = super::mark_done_ids(&todos, &ids, true).unwrap();
+            assert_eq!(marked, [super::Marked::Done]);
+
+            let marked = super::mark_done_ids(&todos, &ids, true).unwrap();
+            assert_eq!(marked, [super::Marked::NotDone]);
+            assert!(!todos.get(&ids[0]).unwrap().done);
+        }
+
+        #[test]
+        fn done_toggles_only_when_configured_to() {
+            let todos = Todos::new_inmemory();
+            let id = todos.add_message("1").unwrap().id.0.to_string();
+            todos.set_done(&id, true).unwrap();
+            let ids = [id.clone()];
+
+            let marked = super::mark_done_ids(&todos, &ids, false).unwrap();
+            assert_eq!(marked, [super::Marked::AlreadyDone]);
+            assert!(todos.get(&id).unwrap().done, "stays done");
+
+            super::mark_done_ids(&todos, &ids, true).unwrap();
+            assert!(!todos.get(&id).unwrap().done, "toggled back");
+        }
+    }
+}
+
+mod timefilter {
+    use chrono::{Local, NaiveDate};
+    use clap::Args;
+    use todo::{Todo, TodoTime};
+
+    #[derive(Debug, Args)]
+    pub struct CreatedFilter {
+        /// Only todos created at or after this time (RFC 3339, YYYY-MM-DD, or ago e.g. 7d).
+        #[arg(long)]
+        since: Option<TodoTime>,
+
+        /// Only todos created at or before this time (RFC 3339, YYYY-MM-DD, or ago e.g. 7d).
+        /// A date counts up to the end of that day.
+        #[arg(long, value_parser = parse_until)]
+        until: Option<TodoTime>,
+
+        /// Only todos created on this day (YYYY-MM-DD), in local time.
+        #[arg(long, value_name = "DATE")]
+        on: Option<NaiveDate>,
+    }
+
+    impl CreatedFilter {
+        pub fn matches(&self, todo: &Todo) -> bool {
+            todo.created_between(self.since.as_ref(), self.until.as_ref())
+                && self
+                    .on
+                    .is_none_or(|on| todo.created_at.date_in(&Local) == on)
+        }
+    }
+
+    /// Like any other time, except that a date is the end of that day rather than its start, so
+    /// the todos created on it are included.
+    fn parse_until(s: &str) -> anyhow::Result<TodoTime> {
+        match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            Ok(date) => TodoTime::end_of_local_day(date),
+            Err(_) => s.parse(),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use chrono::TimeZone;
+        use clap::Parser;
+
+        use super::*;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            created: CreatedFilter,
+        }
+
+        fn created_at(time: chrono::DateTime<Local>) -> Todo {
+            Todo {
+                created_at: time.to_rfc3339().parse().unwrap(),
+                ..Todo::new("one".into())
+            }
+        }
+
+        #[test]
+        fn until_a_date_includes_that_whole_day() {
+            let filter = |args: &[&str]| Cli::parse_from([&["cli"], args].concat()).created;
+            let midday = created_at(Local.with_ymd_and_hms(2024, 3, 5, 12, 30, 0).unwrap());
+            let next_day = created_at(Local.with_ymd_and_hms(2024, 3, 6, 0, 0, 0).unwrap());
+
+            let until = filter(&["--until", "2024-03-05"]);
+            assert!(until.matches(&midday));
+            assert!(!until.matches(&next_day));
+
+            assert!(!filter(&["--until", "2024-03-04"]).matches(&midday));
+            assert!(!filter(&["--since", "2024-03-06"]).matches(&midday));
+            assert!(filter(&["--until", "2024-03-05T12:30:00Z"])
+                .matches(&created_at(Local.timestamp_opt(0, 0).unwrap())));
+        }
+    }
+}
+
+mod ls {
+    use std::{
+        borrow::Cow,
+        collections::HashSet,
+        io::{ErrorKind, Write},
+    };
+
+    use clap::{Args, ValueEnum};
+    use colored::{Color, ColoredString, Colorize};
+    use todo::{Todo, TodoID, Todos};
+
+    use crate::config::{self, DefaultSort, Theme};
+    use crate::timefilter::CreatedFilter;
+
+    #[derive(Debug, Clone, Copy, ValueEnum)]
+    pub enum SortBy {
+        /// Pinned first, then most urgent, then oldest.
+        Smart,
+        /// The list's own order, top to bottom.
+        Order,
+        /// Most recently changed first.
+        Updated,
+        /// Oldest first.
+        Created,
+    }
+
+    impl From<DefaultSort> for SortBy {
+        fn from(value: DefaultSort) -> Self {
+            match value {
+                DefaultSort::Smart => SortBy::Smart,
+                DefaultSort::Manual => SortBy::Order,
+                DefaultSort::Created => SortBy::Created,
+            }
+        }
+    }
+
+    impl SortBy {
+        pub fn sort(self, list: &mut [Todo]) {
+            match self {
+                SortBy::Smart => todo::sort_smart(list),
+                SortBy::Order => {}
+                SortBy::Updated => list.sort_by(|a, b| b.updated_at.cmp(&a.updated_at)),
+                SortBy::Created => list.sort_by(|a, b| a.created_at.cmp(&b.created_at)),
+            }
+        }
+    }
+
+    #[derive(Debug, Args)]
+    pub struct LsArgs {
+        /// Show todos that are done as well, and whole ids.
+        #[arg(short, long)]
+        pub full: bool,
+
+        /// Show only the todo messages, without the progress summary.
+        #[arg(short, long)]
+        pub quiet: bool,
+
+        /// Show only todos that are done, with when they were last changed.
+        #[arg(long, conflicts_with = "full")]
+        pub done_only: bool,
+
+        /// Print only whole ids, one per line, e.g. for `xargs mynd done`.
+        #[arg(long, conflicts_with = "quiet")]
+        pub ids_only: bool,
+
+        /// Print a line per todo for scripts, in a format that won't change:
+        /// `id<TAB>done<TAB>created_at<TAB>message`, with done as 0 or 1, created_at in RFC 3339,
+        /// and tabs, newlines, carriage returns and backslashes in the message escaped as \t, \n, \r
+        /// and \\.
+        #[arg(long, conflicts_with_all = ["quiet", "ids_only"])]
+        pub porcelain: bool,
+
+        #[command(flatten)]
+        pub created: CreatedFilter,
+
+        /// Only todos whose message spans more than one line.
+        #[arg(long, conflicts_with = "single_line")]
+        pub multiline: bool,
+
+        /// Only todos whose message is one line.
+        #[arg(long)]
+        pub single_line: bool,
+
+        /// What order to list todos in [default: the `default_sort` configured]
+        #[arg(long, value_enum)]
+        pub sort: Option<SortBy>,
+
+        /// When to color the output.
+        #[arg(long, value_enum, default_value_t)]
+        pub color: ColorWhen,
+
+        /// Show how long ago todos were created, e.g. "3 hours ago", instead of the date.
+        #[arg(long)]
+        pub relative: bool,
+
+        /// List in the opposite order, after sorting.
+        #[arg(long)]
+        pub reverse: bool,
+
+        /// Cut messages longer than this many characters short, with an ellipsis.
+        #[arg(long, value_name = "N")]
+        pub truncate: Option<usize>,
+    }
+
+    #[derive(Debug, Clone, Copy, Default, ValueEnum)]
+    pub enum ColorWhen {
+        /// When writing to a terminal, and `NO_COLOR` isn't set.
+        #[default]
+        Auto,
+        Always,
+        Never,
+    }
+
+    impl ColorWhen {
+        fn apply(self) {
+            match self {
+                ColorWhen::Auto => {}
+                ColorWhen::Always => colored::control::set_override(true),
+                ColorWhen::Never => colored::control::set_override(false),
+            }
+        }
+    }
+
+    /// Brighter and redder the more urgent a todo is; `None` for the usual pending color.
+    fn urgency_color(priority: u8) -> Option<Color> {
+        match priority {
+            0 => None,
+            1 => Some(Color::BrightYellow),
+            2 => Some(Color::Red),
+            _ => Some(Color::BrightRed),
+        }
+    }
+
+    /// The first `max` characters of `message`, and an ellipsis if that's not all of it.
+    fn truncated(message: &str, max: usize) -> Cow<'_, str> {
+        match message.char_indices().nth(max) {
+            Some((end, _)) => Cow::Owned(format!("{}…", &message[..end])),
+            None => Cow::Borrowed(message),
+        }
+    }
+
+    /// The colors from a [Theme], resolved.
+    #[derive(Debug, Default)]
+    struct Palette {
+        pending: Option<Color>,
+        done: Option<Color>,
+        id: Option<Color>,
+        time: Option<Color>,
+    }
+
+    impl Palette {
+        fn from_theme(theme: &Theme) -> Self {
+            let color = |field: &str, name: &Option<String>| {
+                let name = name.as_deref()?;
+                name.parse::<Color>()
+                    .map_err(|_| {
+                        eprintln!(
+                            "[WARN] unknown color for theme.{field}: {name}, using the default"
+                        )
+                    })
+                    .ok()
+            };
+
+            Self {
+                pending: color("pending", &theme.pending),
+                done: color("done", &theme.done),
+                id: color("id", &theme.id),
+                time: color("time", &theme.time),
+            }
+        }
+
+        /// Style `text`, which is all or some of `todo`'s message, by how `todo` stands.
+        fn message(&self, todo: &Todo, text: &str) -> ColoredString {
+            if todo.done {
+                let message = text.strikethrough();
+                match self.done {
+                    Some(color) => message.color(color),
+                    None => message.dimmed(),
+                }
+            } else {
+                let color = urgency_color(todo.priority)
+                    .or(self.pending)
+                    .unwrap_or(Color::Yellow);
+                text.color(color)
+            }
+        }
+
+        fn id(&self, id: &str) -> ColoredString {
+            match self.id {
+                Some(color) => id.color(color),
+                None => id.dimmed(),
+            }
+        }
+
+        fn time(&self, time: &str) -> ColoredString {
+            match self.time {
+                Some(color) => time.color(color),
+                None => time.dimmed(),
+            }
+        }
+    }
+
+    /// Short ids, unless asked for the whole thing, or the short id is shared by another todo.
+    fn display_id<'a>(id: &'a TodoID, full: bool, shared: &HashSet<&str>) -> &'a str {
+        if full || shared.contains(id.short()) {
+            return &id.0;
+        }
+        id.short()
+    }
+
+    /// Short ids that more than one todo has.
+    fn shared_short_ids(todos: &[Todo]) -> HashSet<&str> {
+        let mut seen = HashSet::new();
+        todos
+            .iter()
+            .map(|t| t.id.short())
+            .filter(|short| !seen.insert(*short))
+            .collect()
+    }
+
+    /// A line of `ls --porcelain`. Scripts rely on this; don't change it.
+    fn porcelain_line(todo: &Todo) -> String {
+        let mut message = String::with_capacity(todo.message.len());
+        for c in todo.message.chars() {
+            match c {
+                '\\' => message.push_str("\\\\"),
+                '\t' => message.push_str("\\t"),
+                '\n' => message.push_str("\\n"),
+                '\r' => message.push_str("\\r"),
+                c => message.push(c),
+            }
+        }
+
+        format!(
+            "{}\t{}\t{}\t{}",
+            todo.id.0,
+            todo.done as u8,
+            todo.created_at.format_utc("%Y-%m-%dT%H:%M:%S%.fZ"),
+            message
+        )
+    }
+
+    impl LsArgs {
+        fn shows(&self, todo: &Todo) -> bool {
+            let by_done = if self.done_only {
+                todo.done
+            } else {
+                self.full || !todo.done
+            };
+
+            by_done
+                && self.created.matches(todo)
+                && (!self.multiline || todo.is_multiline())
+                && (!self.single_line || !todo.is_multiline())
+        }
+
+        /// Put the list in the order to show it.
+        fn arrange(&self, list: &mut [Todo], sort: SortBy) {
+            sort.sort(list);
+            if self.reverse {
+                list.reverse();
+            }
+        }
+
+        fn ids_only_output(&self, list: &[Todo]) -> String {
+            list.iter()
+                .filter(|t| self.shows(t))
+                .map(|t| &*t.id.0)
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        pub fn handle(self) -> anyhow::Result<()> {
+            self.color.apply();
+            let todos = Todos::load_up_with_persistor();
+
+            let cfg = config::load_config_or_default();
+            let sort = self.sort.unwrap_or(cfg.default_sort.into());
+
+            let mut list = todos.get_all()?;
+            self.arrange(&mut list, sort);
+
+            if self.porcelain {
+                for todo in list.iter().filter(|t| self.shows(t)) {
+                    println!("{}", porcelain_line(todo));
+                }
+                return Ok(());
+            }
+
+            if self.ids_only {
+                let ids = self.ids_only_output(&list);
+                if ids.is_empty() {
+                    return Ok(());
+                }
+                // e.g. `| head -1` stops reading after the first id, which is fine
+                return match writeln!(std::io::stdout().lock(), "{ids}") {
+                    Err(err) if err.kind() == ErrorKind::BrokenPipe => Ok(()),
+                    result => Ok(result?),
+                };
+            }
+
+            let palette = Palette::from_theme(&cfg.theme);
+            let shared = shared_short_ids(&list);
+            for short in &shared {
+                eprintln!(
+                    "[WARN] more than one todo has the short id {short}, showing their full ids"
+                );
+            }
+
+            list.iter().filter(|t| self.shows(t)).for_each(|t| {
+                if !self.quiet {
+                    let id = display_id(&t.id, self.full, &shared);
+                    eprintln!("{}      {}", "id:".dimmed(), palette.id(id));
+                    let time = if self.relative {
+                        t.created_at.to_relative_string()
+                    } else {
+                        t.created_at.to_local_date_string()
+                    };
+                    eprintln!("{}    {}", "time:".dimmed(), palette.time(&time));
+                    if self.done_only {
+                        let updated = if self.relative {
+                            t.updated_at.to_relative_string()
+                        } else {
+                            t.updated_at.to_local_date_string()
+                        };
+                        eprintln!("{} {}", "updated:".dimmed(), palette.time(&updated));
+                    }
+                }
+
+                let text = match self.truncate {
+                    Some(max) => truncated(&t.message, max),
+                    None => Cow::Borrowed(t.message.as_str()),
+                };
+                let message = palette.message(t, &text);
+
+                if !self.quiet {
+                    println!(
+                        "{} {}{}{}",
+                        "message:".dimmed(),
+                        "\"".dimmed(),
+                        message,
+                        "\"".dimmed()
+                    );
+                } else {
+                    println!("{}", message);
+                }
+
+                if !self.quiet {
+                    eprintln!()
+                }
+            });
+
+            if !self.quiet {
+                let stats = todos.stats()?;
+                eprintln!("{} {}", stats.progress_bar(20).dimmed(), stats);
+            }
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use clap::Parser;
+        use todo::{Todo, TodoID, Todos};
+
+        use crate::config::Theme;
+
+        use super::{display_id, porcelain_line, shared_short_ids, Palette, SortBy};
+
+        #[test]
+        fn urgent_todos_are_redder() {
+            colored::control::set_override(true);
+            let palette = Palette::default();
+            let urgent = Todo {
+                priority: 3,
+                ..Todo::new("fix prod".to_string())
+            };
+            let someday = Todo::new("learn the banjo".to_string());
+
+            assert_eq!(
+                palette.message(&urgent, &urgent.message).to_string(),
+                "\x1b[91mfix prod\x1b[0m"
+            );
+            assert_eq!(
+                palette.message(&someday, &someday.message).to_string(),
+                "\x1b[33mlearn the banjo\x1b[0m"
+            );
+        }
+
+        #[test]
+        fn theme_colors_pending_todos() {
+            colored::control::set_override(true);
+            let todo = Todo::new("water the plants".to_string());
+
+            let default = Palette::from_theme(&Theme::default());
+            assert_eq!(
+                default.message(&todo, &todo.message).to_string(),
+                "\x1b[33mwater the plants\x1b[0m"
+            );
+
+            let themed = Palette::from_theme(&Theme {
+                pending: Some("bright blue".to_string()),
+                ..Theme::default()
+            });
+            assert_eq!(
+                themed.message(&todo, &todo.message).to_string(),
+                "\x1b[94mwater the plants\x1b[0m"
+            );
+
+            let unknown = Palette::from_theme(&Theme {
+                pending: Some("not a color".to_string()),
+                ..Theme::default()
+            });
+            assert_eq!(
+                unknown.message(&todo, &todo.message).to_string(),
+                default.message(&todo, &todo.message).to_string()
+            );
+        }
+
+        #[test]
+        fn porcelain_has_a_stable_layout() {
+            let todo = Todo {
+                id: "abc123".into(),
+                done: true,
+                created_at: "2024-02-03T04:05:06Z".parse().unwrap(),
+                ..Todo::new("tab\there\nnew line \\ slash".to_string())
+            };
+
+            assert_eq!(
+                porcelain_line(&todo),
+                "abc123\t1\t2024-02-03T04:05:06Z\ttab\\there\\nnew line \\\\ slash"
+            );
+        }
+
+        #[test]
+        fn sorts_backdated_todos_by_creation() {
+            let todos = Todos::new_inmemory();
+            todos.add_message("today").unwrap();
+            todos
+                .add_with_time("last year", "365d".parse().unwrap())
+                .unwrap();
+            todos
+                .add_with_time("years ago", "2024-01-01".parse().unwrap())
+                .unwrap();
+
+            let mut list = todos.get_all().unwrap();
+            SortBy::Created.sort(&mut list);
+
+            let messages = list.iter().map(|t| &*t.message).collect::<Vec<_>>();
+            assert_eq!(messages, vec!["years ago", "last year", "today"]);
+        }
+
+        #[test]
+        fn shows_short_ids_unless_full_or_shared() {
+            let todo = Todo::new("1".to_string());
+            let none = Default::default();
+
+            assert_eq!(display_id(&todo.id, false, &none), todo.id.short());
+            assert_eq!(display_id(&todo.id, false, &none).len(), TodoID::SHORT_LEN);
+            assert_eq!(display_id(&todo.id, true, &none), &*todo.id.0);
+
+            let twins = [
+                Todo {
+                    id: "abcd1234-one".into(),
+                    ..Todo::new("a".to_string())
+                },
+                Todo {
+                    id: "abcd1234-two".into(),
+                    ..Todo::new("b".to_string())
+                },
+                todo.clone(),
+            ];
+            let shared = shared_short_ids(&twins);
+
+            assert_eq!(shared.len(), 1);
+            assert!(shared.contains("abcd1234"));
+            assert_eq!(display_id(&twins[0].id, false, &shared), "abcd1234-one");
+            assert_eq!(display_id(&todo.id, false, &shared), todo.id.short());
+        }
+
+        #[test]
+        fn ids_only_lists_the_filtered_ids() {
+            let list = [
+                Todo::new("1".to_string()),
+                Todo {
+                    done: true,
+                    ..Todo::new("2".to_string())
+                },
+                Todo::new("3".to_string()),
+            ];
+
+            let ls = |args: &[&str]| {
+                let cli =
+                    crate::Cli::try_parse_from(["todo", "ls", "--ids-only"].iter().chain(args))
+                        .unwrap();
+                match cli.command {
+                    Some(crate::Command::Ls(ls)) => ls,
+                    command => panic!("expected ls, got: {command:?}"),
+                }
+            };
+
+            assert_eq!(
+                ls(&[]).ids_only_output(&list),
+                format!("{}\n{}", list[0].id.0, list[2].id.0)
+            );
+            assert_eq!(
+                ls(&["--full"]).ids_only_output(&list),
+                format!("{}\n{}\n{}", list[0].id.0, list[1].id.0, list[2].id.0)
+            );
+        }
+
+        #[test]
+        fn done_only_shows_just_the_done() {
+            let list = [
+                Todo::new("pending".to_string()),
+                Todo {
+                    done: true,
+                    ..Todo::new("finished".to_string())
+                },
+                Todo::new("also pending".to_string()),
+                Todo {
+                    done: true,
+                    ..Todo::new("also finished".to_string())
+                },
+            ];
+
+            let cli = crate::Cli::try_parse_from(["todo", "ls", "--done-only"]).unwrap();
+            let Some(crate::Command::Ls(ls)) = cli.command else {
+                panic!("expected ls");
+            };
+            let shown: Vec<_> = list
+                .iter()
+                .filter(|t| ls.shows(t))
+                .map(|t| t.message.as_str())
+                .collect();
+
+            assert_eq!(shown, vec!["finished", "also finished"]);
+            assert!(crate::Cli::try_parse_from(["todo", "ls", "--done-only", "--full"]).is_err());
+        }
+
+        #[test]
+        fn reverse_flips_the_sorted_order() {
+            let at = |message: &str, time: &str| Todo {
+                created_at: time.parse().unwrap(),
+                ..Todo::new(message.to_string())
+            };
+            let list = [
+                at("middle", "2024-02-01T00:00:00Z"),
+                at("newest", "2024-03-01T00:00:00Z"),
+                at("oldest", "2024-01-01T00:00:00Z"),
+            ];
+            let arranged = |args: &[&str]| {
+                let cli = crate::Cli::try_parse_from(["todo", "ls"].iter().chain(args)).unwrap();
+                let Some(crate::Command::Ls(ls)) = cli.command else {
+                    panic!("expected ls");
+                };
+                let mut list = list.to_vec();
+                ls.arrange(&mut list, ls.sort.unwrap_or(super::SortBy::Order));
+                list.into_iter().map(|t| t.message).collect::<Vec<_>>()
+            };
+
+            assert_eq!(arranged(&["--reverse"]), vec!["oldest", "newest", "middle"]);
+            assert_eq!(
+                arranged(&["--sort", "created", "--reverse"]),
+                vec!["newest", "middle", "oldest"]
+            );
+        }
+
+        #[test]
+        fn truncates_on_char_boundaries() {
+            let message = "añadir más café ☕ y pão de queijo 🧀 para todos";
+
+            assert_eq!(super::truncated(message, 16), "añadir más café …");
+            assert_eq!(super::truncated(message, 17), "añadir más café ☕…");
+            assert_eq!(super::truncated("short", 5), "short");
+            assert_eq!(super::truncated("🧀🧀", 0), "…");
+        }
+
+        #[test]
+        fn filters_by_line_count() {
+            let list = [
+                Todo::new("one line".to_string()),
+                Todo::new("two\nlines".to_string()),
+                Todo::new("also one".to_string()),
+                Todo::new("three\nmore\nlines".to_string()),
+            ];
+
+            let shown = |args: &[&str]| {
+                let cli = crate::Cli::try_parse_from(["todo", "ls"].iter().chain(args)).unwrap();
+                let Some(crate::Command::Ls(ls)) = cli.command else {
+                    panic!("expected ls");
+                };
+                list.iter()
+                    .filter(|t| ls.shows(t))
+                    .map(|t| t.message.as_str())
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                shown(&["--multiline"]),
+                vec!["two\nlines", "three\nmore\nlines"]
+            );
+            assert_eq!(shown(&["--single-line"]), vec!["one line", "also one"]);
+            assert_eq!(shown(&[]).len(), 4);
+            assert!(
+                crate::Cli::try_parse_from(["todo", "ls", "--multiline", "--single-line"]).is_err()
+            );
+        }
+    }
+}
+
+mod show {
+    use clap::Args;
+    use colored::Colorize;
+    use todo::Todos;
+
+    #[derive(Debug, Args)]
+    pub struct ShowArgs {
+        /// Id of the todo to show.
+        id: String,
+    }
+
+    impl ShowArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+            let todo = todos.get(&self.id)?;
+
+            println!("{}      {}", "id:".dimmed(), todo.id.0);
+            println!(
+                "{}    {}",
+                "time:".dimmed(),
+                todo.created_at.to_local_date_string()
+            );
+            println!("{}    {}", "done:".dimmed(), todo.done);
+            println!("{}", "message:".dimmed());
+            println!("{}", todo.message);
+
+            Ok(())
+        }
+    }
+}
+
+mod edit {
+    use std::{
+        fs::File,
+        io::{BufWriter, Write},
+        process::ExitStatus,
+    };
+
+    use anyhow::{anyhow, Context};
+    use clap::Args;
+    use todo::{persist::TodosDatabase, TodoID, Todos};
+
+    use crate::{config, lang};
+
+    /// Set for the editor, so that a language server it starts leaves saving the todos to
+    /// `edit`, which only does so once the editor exits cleanly.
+    pub const EDIT_SESSION_VAR: &str = "MYND_EDIT_SESSION";
+
+    #[derive(Debug, Args, Default)]
+    pub struct Edit {
+        /// Editor command to use instead of the configured one or $EDITOR.
+        #[arg(long)]
+        editor: Option<String>,
+    }
+
+    const DEFAULT_EDITOR: &str = if cfg!(windows) { "notepad" } else { "vi" };
+
+    /// Pick the editor to use, in order of precedence: the --editor flag, the `editor` config
+    /// value, $EDITOR, then a platform default.
+    fn resolve_editor(
+        flag: Option<String>,
+        configured: Option<String>,
+        env: Option<String>,
+    ) -> String {
+        [flag, configured, env]
+            .into_iter()
+            .flatten()
+            .find(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+    }
+
+    /// Whether to go with what was edited, by how the editor exited. Failing, or quitting with
+    /// an error on purpose like vim's `:cq`, means the edits are to be thrown away.
+    fn keeps_edits(status: ExitStatus) -> bool {
+        status.success()
+    }
+
+    /// Lines of a multiline todo are all indented the same on top of however they're indented
+    /// in the message, so they can be read back with their relative indentation.
+    const BLOCK_INDENT: &str = "  ";
+
+    /// Multiline todos are written between braces, or as a heredoc when braces are off or can't
+    /// hold the message, so they read back the same with the configured delimiters.
+    fn write_todo(
+        out: &mut impl Write,
+        keyword: &str,
+        message: &str,
+        delimiters: config::BlockDelimiters,
+    ) -> anyhow::Result<()> {
+        write!(out, "{keyword} ")?;
+
+        if message.lines().count() > 1 {
+            let (open, close) = if delimiters.braces && !message.contains('}') {
+                ("{".to_string(), "}".to_string())
+            } else if delimiters.heredoc {
+                let tag = heredoc_tag(message);
+                (format!("<<{tag}"), tag)
+            } else {
+                return Err(anyhow!(
+                    "can't write a multiline todo with the configured block delimiters; \
+                    enable heredoc to edit it: {}",
+                    message.lines().next().unwrap_or_default()
+                ));
+            };
+
+            writeln!(out, "{open}")?;
+            for line in message.lines() {
+                writeln!(out, "{BLOCK_INDENT}{}", line)?;
+            }
+            writeln!(out, "{close}")?;
+        } else {
+            writeln!(out, "{}", message)?;
+        }
+
+        writeln!(out)?;
+
+        Ok(())
+    }
+
+    /// `END`, or with a number after it when the message has a line that would end it early.
+    fn heredoc_tag(message: &str) -> String {
+        std::iter::once("END".to_string())
+            .chain((1..).map(|n| format!("END{n}")))
+            .find(|tag| !message.lines().any(|line| line.trim() == tag))
+            .expect("some tag isn't in the message")
+    }
+
+    /// Take in the edited document: each todo written to it and taken out is removed, and the
+    /// rest is applied with [`Todos::apply_todolang_text`]. Nothing is changed if any of it
+    /// can't be read.
+    fn apply_edits<DB: TodosDatabase>(
+        todos: &Todos<DB>,
+        written: &[TodoID],
+        text: &str,
+        cfg: &config::MyndConfig,
+    ) -> anyhow::Result<()> {
+        let text = lang::parse(text, cfg);
+
+        let errors: Vec<_> = text
+            .items
+            .iter()
+            .filter_map(|item| item.as_ref().err())
+            .collect();
+        if !errors.is_empty() {
+            for err in &errors {
+                let start = err.span().start;
+                eprintln!("[ERROR] {}:{}: {err}", start.line + 1, start.col + 1);
+            }
+            return Err(anyhow!(
+                "found {} error(s) in the edited todos; left them as they were",
+                errors.len()
+            ));
+        }
+
+        let applied = todos.apply_todolang_text(text)?;
+
+        for id in written.iter().filter(|id| !applied.ids.contains(id)) {
+            todos.remove(&id.0)?;
+        }
+
+        Ok(())
+    }
+
+    impl Edit {
+        pub fn handle(self) -> anyhow::Result<()> {
+            crate::savefile::ensure_writable()?;
+            let todos = Todos::load_up_with_persistor();
+
+            let temp_filename = "/tmp/mynd-todo.td";
+            let mut file = File::options()
+                .read(true)
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(temp_filename)
+                .map(BufWriter::new)?;
+
+            let cfg = config::load_config_or_default();
+            let keyword = cfg.keywords()[0];
+
+            let mut written = vec![];
+            for todo in todos.get_all()? {
+                write_todo(&mut file, keyword, &todo.message, cfg.block_delimiters)?;
+                written.push(todo.id);
+            }
+
+            drop(file);
+
+            let editor = resolve_editor(
+                self.editor,
+                cfg.editor.clone(),
+                std::env::var("EDITOR").ok(),
+            );
+
+            let exitstatus = std::process::Command::new(&editor)
+                .arg(temp_filename)
+                .env(EDIT_SESSION_VAR, "1")
+                .spawn()
+                .context(anyhow!("failed to open editor: {}", editor))?
+                .wait()?;
+
+            if !keeps_edits(exitstatus) {
+                eprintln!(
+                    "[WARN] the editor didn't exit cleanly ({exitstatus}); discarded the edits"
+                );
+                return Ok(());
+            }
+
+            eprintln!("[INFO] {}", exitstatus);
+
+            let text = std::fs::read_to_string(temp_filename)
+                .context("failed to read the edited todos")?;
+            apply_edits(&todos, &written, &text, &cfg)?;
+
+            todos.flush()?;
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::testing::messages;
+
+        use super::*;
+
+        fn some(s: &str) -> Option<String> {
+            Some(s.to_string())
+        }
+
+        #[test]
+        fn multiline_indentation_survives_an_edit() {
+            use crate::lang::{lexer::Lexer, parser::ast, parser::Parser};
+
+            let message = "def main():\n    if ready:\n        run()\n    done()";
+            let mut written = vec![];
+            write_todo(&mut written, "todo", message, Default::default()).unwrap();
+            let written = String::from_utf8(written).unwrap();
+
+            let parse = |preserve| {
+                let text = Parser::new(Lexer::new(&written))
+                    .preserving_indent(preserve)
+                    .parse();
+                match text.items.into_iter().next() {
+                    Some(Ok(ast::Item::Multiline(item))) => item.message,
+                    items => panic!("expected a multiline todo, got: {items:?}"),
+                }
+            };
+
+            assert_eq!(parse(true), message);
+            assert_eq!(parse(false), "def main():\nif ready:\nrun()\ndone()");
+        }
+
+        #[test]
+        fn multiline_todos_read_back_with_each_delimiter_setting() {
+            use config::BlockDelimiters;
+
+            let plain = "one\ntwo";
+            let with_brace = "fn main() {\n    run()\n}";
+            let with_end = "one\nEND\nEND1\ntwo";
+
+            let read_back = |braces, heredoc, message: &str| {
+                let cfg = config::MyndConfig {
+                    block_delimiters: BlockDelimiters { braces, heredoc },
+                    preserve_indent: true,
+                    ..Default::default()
+                };
+                let mut written = vec![];
+                write_todo(&mut written, "todo", message, cfg.block_delimiters)?;
+                let written = String::from_utf8(written).unwrap();
+
+                let todos = Todos::new_inmemory();
+                apply_edits(&todos, &[], &written, &cfg)?;
+                anyhow::Ok(messages(&todos))
+            };
+
+            assert_eq!(read_back(true, false, plain).unwrap(), [plain]);
+            assert_eq!(read_back(false, true, plain).unwrap(), [plain]);
+            assert_eq!(read_back(true, true, plain).unwrap(), [plain]);
+
+            assert_eq!(read_back(false, true, with_brace).unwrap(), [with_brace]);
+            assert_eq!(read_back(true, true, with_brace).unwrap(), [with_brace]);
+            assert!(read_back(true, false, with_brace).is_err());
+
+            assert_eq!(read_back(false, true, with_end).unwrap(), [with_end]);
+        }
+
+        #[test]
+        fn failed_editor_discards_the_edits() {
+            use std::os::unix::process::ExitStatusExt;
+
+            assert!(keeps_edits(ExitStatus::from_raw(0)));
+            assert!(
+                !keeps_edits(ExitStatus::from_raw(1 << 8)),
+                "exit code 1, e.g. :cq"
+            );
+            assert!(!keeps_edits(ExitStatus::from_raw(9)), "killed by a signal");
+        }
+
+        #[test]
+        fn applies_the_edited_todos() {
+            let cfg = config::MyndConfig::default();
+            let todos = Todos::new_inmemory();
+            let written: Vec<_> = ["1", "2", "3"]
+                .into_iter()
+                .map(|m| todos.add_message(m).unwrap().id)
+                .collect();
+
+            apply_edits(&todos, &written, "todo 3\n\ntodo [x] 1\n\ntodo 4\n", &cfg).unwrap();
+
+            let state: Vec<_> = todos
+                .get_all()
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.message, t.done))
+                .collect();
+            assert_eq!(
+                state,
+                vec![("3".into(), false), ("1".into(), true), ("4".into(), false)]
+            );
+
+            let written: Vec<_> = todos.get_all().unwrap().into_iter().map(|t| t.id).collect();
+            assert!(apply_edits(&todos, &written, "todo 3\nstray text\n", &cfg).is_err());
+            assert_eq!(messages(&todos), vec!["3", "1", "4"]);
+        }
+
+        #[test]
+        fn resolves_editor_by_precedence() {
+            assert_eq!(resolve_editor(some("hx"), some("nvim"), some("nano")), "hx");
+            assert_eq!(resolve_editor(None, some("nvim"), some("nano")), "nvim");
+            assert_eq!(resolve_editor(None, None, some("nano")), "nano");
+            assert_eq!(resolve_editor(None, None, None), DEFAULT_EDITOR);
+        }
+    }
+}
+
+mod pin {
+    use clap::Args;
+    use todo::Todos;
+
+    #[derive(Args, Debug)]
+    pub struct PinArgs {
+        /// Id(s) of the todo(s) to pin, or unpin if they already are.
+        #[arg(required = true)]
+        ids: Vec<String>,
+    }
+
+    impl PinArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+
+            for id in self.ids {
+                if todos.toggle_pin(&id)? {
+                    eprintln!("[INFO] pinned todo id: {}", id);
+                } else {
+                    eprintln!("[INFO] unpinned todo id: {}", id);
+                }
+            }
+
+            todos.flush()?;
+
+            Ok(())
+        }
+    }
+}
+
+mod prioritize {
+    use clap::Args;
+    use todo::{persist::TodosDatabase, Todos};
+
+    use crate::prompt::Prompt;
+
+    #[derive(Args, Debug)]
+    pub struct PrioritizeArgs {
+        /// Set the priority of todos whose message contains this text.
+        #[arg(short, long)]
+        matching: String,
+
+        /// How urgent they are; higher is more urgent, 0 for not at all.
+        #[arg(short, long)]
+        level: u8,
+
+        /// Don't ask for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    }
+
+    impl PrioritizeArgs {
+        pub fn handle(self, prompt: Prompt) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+
+            let matches = todos.search(&self.matching)?;
+            if matches.is_empty() {
+                eprintln!("[INFO] no todos match: {:?}", self.matching);
+                return Ok(());
+            }
+
+            for todo in &matches {
+                eprintln!("  {}", todo.message);
+            }
+
+            let question = format!(
+                "set the priority of {} todo(s) to {}?",
+                matches.len(),
+                self.level
+            );
+            if !self.yes && !prompt.confirm(&question)? {
+                eprintln!("[INFO] aborted");
+                return Ok(());
+            }
+
+            let count = prioritize(&todos, &self.matching, self.level)?;
+            eprintln!("[INFO] set the priority of {} todo(s)", count);
+
+            Ok(())
+        }
+    }
+
+    /// Set the priority of the todos matching `query`, saving once. Returns how many changed.
+    fn prioritize<DB: TodosDatabase>(
+        todos: &Todos<DB>,
+        query: &str,
+        level: u8,
+    ) -> anyhow::Result<usize> {
+        let mut count = 0;
+        for todo in todos.search(query)? {
+            if todos.set_priority(&todo.id.0, level)? {
+                count += 1;
+            }
+        }
+
+        todos.flush()?;
+
+        Ok(count)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::{testing::CountingDB, Todos};
+
+        #[test]
+        fn prioritizes_only_the_matches() {
+            let todos = Todos::new(CountingDB::default());
+            for message in ["fix the sink", "call mom", "Fix the fence"] {
+                todos.add_message(message).unwrap();
+            }
+
+            assert_eq!(super::prioritize(&todos, "fix", 2).unwrap(), 2);
+            assert_eq!(todos.db.saves(), 1);
+
+            let priorities: Vec<_> = todos
+                .get_all()
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.message, t.priority))
+                .collect();
+            assert_eq!(
+                priorities,
+                vec![
+                    ("fix the sink".to_string(), 2),
+                    ("call mom".to_string(), 0),
+                    ("Fix the fence".to_string(), 2)
+                ]
+            );
+
+            assert_eq!(super::prioritize(&todos, "fix", 2).unwrap(), 0);
+        }
+    }
+}
+
+mod clean {
+    use clap::Args;
+    use todo::{persist::TodosDatabase, Todo, Todos};
+
+    use crate::{config, prompt::Prompt, remove::delete_ids};
+
+    #[derive(Args, Debug)]
+    pub struct CleanArgs {
+        /// List the todos that would be deleted, without deleting them.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation, however many there are.
+        #[arg(short, long)]
+        yes: bool,
+    }
+
+    impl CleanArgs {
+        pub fn handle(self, prompt: Prompt) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+            let threshold = config::load_config_or_default().confirm_delete_over;
+
+            if self.dry_run {
+                let done = done_todos(&todos)?;
+                for todo in &done {
+                    eprintln!("  {}", todo.message);
+                }
+                eprintln!("[INFO] would delete {} done todo(s)", done.len());
+                return Ok(());
+            }
+
+            match clean(&todos, prompt, threshold, self.yes)? {
+                Some(deleted) => eprintln!("[INFO] deleted {} done todo(s)", deleted.len()),
+                None => eprintln!("[INFO] aborted"),
+            }
+
+            Ok(())
+        }
+    }
+
+    fn done_todos<DB: TodosDatabase>(todos: &Todos<DB>) -> anyhow::Result<Vec<Todo>> {
+        Ok(todos.get_all()?.into_iter().filter(|t| t.done).collect())
+    }
+
+    /// Delete the done todos, asking first when there are more than `threshold` of them.
+    /// Returns the ones deleted, or `None` when told not to go ahead.
+    fn clean<DB: TodosDatabase>(
+        todos: &Todos<DB>,
+        prompt: Prompt,
+        threshold: usize,
+        yes: bool,
+    ) -> anyhow::Result<Option<Vec<Todo>>> {
+        let done = done_todos(todos)?;
+        let ids: Vec<_> = done.iter().map(|t| t.id.0.to_string()).collect();
+
+        let Some(deleted) = delete_ids(todos, &ids, prompt, threshold, yes)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            done.into_iter()
+                .filter(|t| deleted.iter().any(|id| **id == *t.id.0))
+                .collect(),
+        ))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::{testing::messages, Todos};
+
+        use super::{clean, done_todos};
+        use crate::prompt::Prompt;
+
+        #[test]
+        fn cleans_only_done_todos() {
+            let todos = Todos::new_inmemory();
+            for message in ["1", "2", "3"] {
+                todos.add_message(message).unwrap();
+            }
+            for message in ["1", "3"] {
+                todos
+                    .set_done(&todo::TodoID::hash_message(message).0, true)
+                    .unwrap();
+            }
+
+            let no_input = Prompt::new(true);
+
+            assert_eq!(done_todos(&todos).unwrap().len(), 2);
+            assert_eq!(
+                messages(&todos),
+                vec!["1", "2", "3"],
+                "a dry run leaves them"
+            );
+
+            assert!(clean(&todos, no_input, 1, false).is_err());
+            assert_eq!(messages(&todos), vec!["1", "2", "3"], "over the threshold");
+
+            let cleaned = clean(&todos, no_input, 2, false).unwrap().unwrap();
+            assert_eq!(cleaned.len(), 2);
+            assert_eq!(messages(&todos), vec!["2"]);
+            assert!(clean(&todos, no_input, 2, false)
+                .unwrap()
+                .unwrap()
+                .is_empty());
+        }
+    }
+}
+
+mod remove {
+    use clap::Args;
+    use todo::{persist::TodosDatabase, Todos};
+
+    use crate::{config, pick, prompt::Prompt};
+
+    #[derive(Args, Debug)]
+    pub struct RemoveArgs {
+        /// Id(s) of the todo(s) to delete.
+        ids: Vec<String>,
+
+        #[command(flatten)]
+        pick: pick::Extreme,
+
+        /// Delete every todo, including the ones that aren't done.
+        #[arg(long, conflicts_with_all = ["ids", "oldest", "newest"])]
+        all: bool,
+
+        /// Don't ask for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    }
+
+    impl RemoveArgs {
+        pub fn handle(self, prompt: Prompt) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+            let threshold = config::load_config_or_default().confirm_delete_over;
+            self.remove_from(&todos, threshold, prompt)
+        }
+
+        /// Asks first when deleting everything, or more than `threshold` todos.
+        fn remove_from<DB: TodosDatabase>(
+            self,
+            todos: &Todos<DB>,
+            threshold: usize,
+            prompt: Prompt,
+        ) -> anyhow::Result<()> {
+            if self.all {
+                let question = format!("delete all {} todo(s)?", todos.get_all()?.len());
+                if !self.yes && !prompt.confirm(&question)? {
+                    eprintln!("[INFO] aborted");
+                    return Ok(());
+                }
+
+                let removed = todos.clear()?;
+                eprintln!("[INFO] deleted {} todo(s)", removed);
+                return Ok(());
+            }
+
+            let ids = if self.pick.is_set() {
+                self.pick.resolve(todos)?.into_iter().collect()
+            } else {
+                self.ids
+            };
+
+            let Some(deleted) = delete_ids(todos, &ids, prompt, threshold, self.yes)? else {
+                eprintln!("[INFO] aborted");
+                return Ok(());
+            };
+
+            for id in deleted {
+                eprintln!("[INFO] deleted todo id: {}", id)
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Delete the todos with `ids` and save; how every command deletes todos a handful at a
+    /// time. Asks first when that's more than `threshold` of them, unless told `yes`.
+    /// Returns the ids deleted, or `None` when told not to go ahead. Ids that fail to be
+    /// deleted are reported and skipped.
+    pub fn delete_ids<'a, DB: TodosDatabase>(
+        todos: &Todos<DB>,
+        ids: &'a [String],
+        prompt: Prompt,
+        threshold: usize,
+        yes: bool,
+    ) -> anyhow::Result<Option<Vec<&'a str>>> {
+        if !prompt.confirm_delete(ids.len(), threshold, yes)? {
+            return Ok(None);
+        }
+
+        let mut deleted = vec![];
+        for id in ids {
+            match todos.remove(id) {
+                Ok(_) => deleted.push(id.as_str()),
+                Err(err) => {
+                    eprintln!("[ERROR] failed to remove todo id: {}", id);
+                    eprintln!("[ERROR] {err:#}")
+                }
+            }
+        }
+
+        todos.flush()?;
+
+        Ok(Some(deleted))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use clap::Parser;
+        use todo::Todos;
+
+        #[test]
+        fn rm_all_yes_empties_the_list() {
+            let todos = Todos::new_inmemory();
+            for message in ["1", "2", "3"] {
+                todos.add_message(message).unwrap();
+            }
+            todos.mark_done_matching("2").unwrap();
+
+            let cli = crate::Cli::try_parse_from(["todo", "rm", "--all", "--yes"]).unwrap();
+            let prompt = cli.prompt();
+            let Some(crate::Command::Rm(rm)) = cli.command else {
+                panic!("expected rm");
+            };
+            rm.remove_from(&todos, 5, prompt).unwrap();
+
+            assert!(todos.get_all().unwrap().is_empty());
+            assert!(crate::Cli::try_parse_from(["todo", "rm", "--all", "some-id"]).is_err());
+        }
+
+        #[test]
+        fn rm_all_without_yes_fails_under_no_input() {
+            let todos = Todos::new_inmemory();
+            todos.add_message("1").unwrap();
+
+            let cli = crate::Cli::try_parse_from(["todo", "rm", "--all", "--no-input"]).unwrap();
+            let prompt = cli.prompt();
+            let Some(crate::Command::Rm(rm)) = cli.command else {
+                panic!("expected rm");
+            };
+
+            let err = rm.remove_from(&todos, 5, prompt).unwrap_err();
+            assert!(err.to_string().contains("--no-input"), "{err:#}");
+            assert_eq!(todos.get_all().unwrap().len(), 1);
+        }
+
+        #[test]
+        fn rm_over_the_threshold_needs_confirming() {
+            let todos = Todos::new_inmemory();
+            let ids: Vec<String> = ["1", "2", "3"]
+                .map(|m| todos.add_message(m).unwrap().id.0.to_string())
+                .to_vec();
+
+            let rm = |extra: &[&str]| {
+                let args = ["todo", "rm", "--no-input"].iter().copied();
+                let args = args.chain(ids.iter().map(String::as_str));
+                let cli = crate::Cli::try_parse_from(args.chain(extra.iter().copied())).unwrap();
+                let prompt = cli.prompt();
+                let Some(crate::Command::Rm(rm)) = cli.command else {
+                    panic!("expected rm");
+                };
+                (rm, prompt)
+            };
+
+            let (no, prompt) = rm(&[]);
+            assert!(no.remove_from(&todos, 2, prompt).is_err());
+            assert_eq!(todos.get_all().unwrap().len(), 3, "nothing deleted");
+
+            let (yes, prompt) = rm(&["--yes"]);
+            yes.remove_from(&todos, 2, prompt).unwrap();
+            assert!(todos.get_all().unwrap().is_empty());
+        }
+    }
+}
+
+mod merge {
+    use std::path::{Path, PathBuf};
+
+    use clap::Args;
+    use todo::{persist::TodosDatabase, Todos};
+
+    use crate::import::read_todos;
+
+    #[derive(Debug, Args)]
+    pub struct MergeArgs {
+        /// The save file to merge in, json or binary by its extension.
+        file: PathBuf,
+    }
+
+    impl MergeArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+            let changed = merge_file(&todos, &self.file)?;
+
+            todos.flush()?;
+
+            eprintln!(
+                "[INFO] added or updated {} todo(s) from {}",
+                changed,
+                self.file.display()
+            );
+
+            Ok(())
+        }
+    }
+
+    /// See [`Todos::merge`] for how todos in both are reconciled.
+    fn merge_file<DB: TodosDatabase>(todos: &Todos<DB>, file: &Path) -> anyhow::Result<usize> {
+        let other = read_todos(file)?;
+        todos.merge(other)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::{persist::jsonfile::write_json, Todo, TodoTime, Todos};
+
+        use super::merge_file;
+
+        #[test]
+        fn merges_an_overlapping_save_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let other = dir.path().join("other.json");
+
+            let todos = Todos::new_inmemory();
+            let shared = todos.add_message("shared").unwrap();
+            let edited = todos.add_message("only edited here").unwrap();
+            todos.add_message("only here").unwrap();
+
+            let done_there = Todo {
+                done: true,
+                updated_at: TodoTime::now(),
+                ..shared
+            };
+            let stale = Todo {
+                done: true,
+                ..edited.clone()
+            };
+            todos.set_priority(&edited.id.0, 2).unwrap();
+            write_json(
+                &other,
+                vec![done_there, stale, Todo::new("only there".to_string())],
+            )
+            .unwrap();
+
+            assert_eq!(merge_file(&todos, &other).unwrap(), 2);
+
+            let merged: Vec<_> = todos
+                .get_all()
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.message, t.done))
+                .collect();
+            assert_eq!(
+                merged,
+                vec![
+                    ("shared".to_string(), true),
+                    ("only edited here".to_string(), false),
+                    ("only here".to_string(), false),
+                    ("only there".to_string(), false),
+                ]
+            );
+        }
+    }
+}
+
+mod import {
+    use std::{
+        ffi::OsStr,
+        path::{Path, PathBuf},
+    };
+
+    use anyhow::{anyhow, Context};
+    use serde::{Deserialize, Serialize};
+    use todo::{
+        persist::{binary, jsonfile, TodosDatabase},
+        Backup, ImportMode, Todo, Todos,
+    };
+
+    use clap::Args;
+
+    /// Json files to import are either just the todos, like `dump`, or an `export --full`.
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum JsonImport {
+        Todos(Vec<Todo>),
+        Backup(Backup),
+    }
+
+    #[derive(Debug, Args)]
+    pub struct ImportArgs {
+        /// from which to read todo items; glob patterns are expanded
+        #[arg(required_unless_present = "from_share")]
+        files: Vec<String>,
+
+        /// Read todo items from a string printed by `share`, instead of from files.
+        #[arg(long, value_name = "STRING", conflicts_with = "files")]
+        from_share: Option<String>,
+
+        /// Replace the current todos with the imported ones, instead of merging them in.
+        #[arg(long)]
+        replace: bool,
+    }
+
+    impl ImportArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let files = expand_globs(&self.files)?;
+
+            let mode = if self.replace {
+                ImportMode::Replace
+            } else {
+                ImportMode::Merge
+            };
+
+            let todos = Todos::load_up_with_persistor();
+
+            if let Some(shared) = &self.from_share {
+                let imported = crate::share::decode(shared)?;
+                let count = imported.len();
+                let added = todos.import(imported, mode)?;
+                todos.flush()?;
+                eprintln!("[INFO] imported {added} new todo(s) of {count} shared");
+                return Ok(());
+            }
+
+            let failures = import_files(&todos, &files, mode)?;
+
+            if failures.is_empty() || mode == ImportMode::Merge {
+                todos.flush()?;
+            }
+
+            if !failures.is_empty() {
+                for (file, err) in &failures {
+                    eprintln!("[ERROR] failed to import {}: {err:#}", file.display());
+                }
+                return Err(anyhow!(
+                    "failed to import {} of {} file(s)",
+                    failures.len(),
+                    files.len()
+                ));
+            }
+
+            Ok(())
+        }
+    }
+
+    fn expand_globs(patterns: &[String]) -> anyhow::Result<Vec<PathBuf>> {
+        let mut files = vec![];
+
+        for pattern in patterns {
+            if !pattern.contains(['*', '?', '[']) {
+                files.push(PathBuf::from(pattern));
+                continue;
+            }
+
+            let mut matched = glob::glob(pattern)
+                .with_context(|| format!("invalid glob pattern: {pattern}"))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if matched.is_empty() {
+                eprintln!("[WARN] no files match: {pattern}");
+            }
+
+            matched.sort();
+            files.extend(matched);
+        }
+
+        Ok(files)
+    }
+
+    /// Import each file in order, carrying on past the ones that fail to be read.
+    /// Replacing reads every file before touching the list, and leaves it as is if any fail.
+    /// Returns the failures.
+    fn import_files<DB: TodosDatabase>(
+        todos: &Todos<DB>,
+        files: &[PathBuf],
+        mode: ImportMode,
+    ) -> anyhow::Result<Vec<(PathBuf, anyhow::Error)>> {
+        if mode == ImportMode::Replace {
+            return replace_with_files(todos, files);
+        }
+
+        let mut failures = vec![];
+
+        for file in files {
+            match read_todos(file) {
+                Ok(imported) => {
+                    let count = imported.len();
+                    let added = todos.import(imported, ImportMode::Merge)?;
+                    eprintln!(
+                        "[INFO] imported {} new todo(s) of {} from {}",
+                        added,
+                        count,
+                        file.display()
+                    );
+                }
+                Err(err) => failures.push((file.clone(), err)),
+            }
+        }
+
+        Ok(failures)
+    }
+
+    fn replace_with_files<DB: TodosDatabase>(
+        todos: &Todos<DB>,
+        files: &[PathBuf],
+    ) -> anyhow::Result<Vec<(PathBuf, anyhow::Error)>> {
+        if files.is_empty() {
+            return Err(anyhow!("no files to replace the todos with"));
+        }
+
+        let mut imported = vec![];
+        let mut failures = vec![];
+
+        for file in files {
+            match read_todos(file) {
+                Ok(todos) => imported.extend(todos),
+                Err(err) => failures.push((file.clone(), err)),
+            }
+        }
+
+        if !failures.is_empty() {
+            return Ok(failures);
+        }
+
+        let count = imported.len();
+        let added = todos.import(imported, ImportMode::Replace)?;
+        eprintln!(
+            "[INFO] replaced the todos with {} todo(s) of {} from {} file(s)",
+            added,
+            count,
+            files.len()
+        );
+
+        Ok(failures)
+    }
+
+    /// Todos from a json, binary or plain text file, by its extension.
+    pub fn read_todos(file: &Path) -> anyhow::Result<Vec<Todo>> {
+        let supported_extensions = &["json", "bin", "txt"].map(OsStr::new);
+
+        let ext = file
+            .extension()
+            .filter(|ext| supported_extensions.contains(ext))
+            .context(anyhow!(
+                "extension is not one of the only supported: {:?}",
+                supported_extensions.map(|s| s.to_string_lossy()),
+            ))
+            .and_then(|e| e.to_str().context("file extension is not in utf-8"));
+
+        let imported_todos;
+
+        match ext {
+                Ok("json") => {
+                    imported_todos = match jsonfile::read_json(file)? {
+                        JsonImport::Todos(todos) => todos,
+                        JsonImport::Backup(backup) => backup.into_todos()?,
+                    };
+                }
+                Ok("bin") => {
+                    let data =
+                        std::fs::read(file).context("failed to read from import file")?;
+                    let (records, _) = binary::strip_checksum(&data);
+                    let (todos, skipped) = binary::get_todos_from_binary_lossy(records);
+
+                    for s in skipped {
+                        eprintln!(
+                            "[WARN] skipped {} byte(s) at offset {} that couldn't be read: {}",
+                            s.len, s.offset, s.reason
+                        );
+                    }
+
+                    imported_todos = todos;
+                }
+                Ok("txt") => {
+                    let text = std::fs::read_to_string(file)
+                        .context("failed to read from import file")?;
+                    imported_todos = crate::todotxt::parse(&text);
+                }
+                Err(err) => {
+                    return Err(err.context("unsupported file extension"))
+                }
+                _ => unreachable!("unreachable assertion failed even though we are[should be] filter out unsupported extensions in an error"),
+            }
+
+        Ok(imported_todos)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::{persist::jsonfile::write_json, testing::messages};
+
+        use super::*;
+
+        #[test]
+        fn imports_many_files_deduped() {
+            let dir = tempfile::tempdir().unwrap();
+            let first = dir.path().join("first.json");
+            let second = dir.path().join("second.json");
+            let broken = dir.path().join("broken.csv");
+
+            std::fs::write(&first, "").unwrap();
+            std::fs::write(&second, "").unwrap();
+            write_json(&first, vec![Todo::new("1".into()), Todo::new("2".into())]).unwrap();
+            write_json(&second, vec![Todo::new("2".into()), Todo::new("3".into())]).unwrap();
+
+            let todos = Todos::new_inmemory();
+            todos.add_message("0").unwrap();
+
+            let failures =
+                import_files(&todos, &[first, broken.clone(), second], ImportMode::Merge).unwrap();
+
+            assert_eq!(failures.len(), 1);
+            assert_eq!(failures[0].0, broken);
+
+            assert_eq!(messages(&todos), vec!["0", "1", "2", "3"]);
+        }
+
+        #[test]
+        fn merging_keeps_todos_done_since_the_export() {
+            let dir = tempfile::tempdir().unwrap();
+            let file = dir.path().join("old.json");
+            write_json(&file, vec![Todo::new("1".into()), Todo::new("2".into())]).unwrap();
+
+            let todos = Todos::new_inmemory();
+            let done = todos.add_message("1").unwrap();
+            todos.mark_done(&done.id.0).unwrap();
+
+            import_files(&todos, std::slice::from_ref(&file), ImportMode::Merge).unwrap();
+
+            let state: Vec<_> = todos
+                .get_all()
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.message, t.done))
+                .collect();
+            assert_eq!(state, vec![("1".into(), true), ("2".into(), false)]);
+
+            import_files(&todos, &[file], ImportMode::Replace).unwrap();
+            assert!(todos.get_all().unwrap().iter().all(|t| !t.done));
+        }
+
+        #[test]
+        fn replacing_with_an_unreadable_file_keeps_the_list() {
+            let dir = tempfile::tempdir().unwrap();
+            let good = dir.path().join("good.json");
+            let missing = dir.path().join("missing.json");
+            write_json(&good, vec![Todo::new("new".into())]).unwrap();
+
+            let todos = Todos::new_inmemory();
+            todos.add_message("kept").unwrap();
+
+            let failures =
+                import_files(&todos, &[good, missing.clone()], ImportMode::Replace).unwrap();
+
+            assert_eq!(failures.len(), 1);
+            assert_eq!(failures[0].0, missing);
+
+            assert_eq!(messages(&todos), vec!["kept"]);
+
+            assert!(import_files(&todos, &[], ImportMode::Replace).is_err());
+            assert_eq!(todos.get_all().unwrap().len(), 1);
+        }
+
+        #[test]
+        fn imports_a_line_per_todo_from_text() {
+            let dir = tempfile::tempdir().unwrap();
+            let file = dir.path().join("todo.txt");
+            std::fs::write(
+                &file,
+                "# groceries\nmilk\n\n  eggs  \n   \n  # not this either\nmilk\nbread # and jam\n",
+            )
+            .unwrap();
+
+            let todos = Todos::new_inmemory();
+            todos.add_message("eggs").unwrap();
+            import_files(&todos, &[file], ImportMode::Merge).unwrap();
+
+            assert_eq!(messages(&todos), vec!["eggs", "milk", "bread # and jam"]);
+        }
+
+        #[test]
+        fn imports_full_exports() {
+            let dir = tempfile::tempdir().unwrap();
+            let file = dir.path().join("backup.json");
+            let exported = vec![Todo::new("1".into()), Todo::new("2".into())];
+
+            let backup = serde_json::to_value(Backup::new(exported.clone())).unwrap();
+            assert_eq!(backup["format"], "mynd-todos");
+            assert_eq!(backup["version"], 1);
+            assert!(backup["exported_at"].is_string());
+
+            std::fs::write(&file, backup.to_string()).unwrap();
+
+            let imported = read_todos(&file).unwrap();
+            assert_eq!(
+                serde_json::to_value(imported).unwrap(),
+                serde_json::to_value(exported).unwrap()
+            );
+
+            let mut foreign = backup.clone();
+            foreign["format"] = "something-else".into();
+            std::fs::write(&file, foreign.to_string()).unwrap();
+            assert!(read_todos(&file).is_err());
+        }
+    }
+}
+
+mod dump {
+    use clap::Args;
+    use todo::{Todo, Todos};
+
+    use crate::timefilter::CreatedFilter;
+
+    #[derive(Debug, Args)]
+    pub struct DumpArgs {
+        /// Only dump undone todo items
+        #[arg(short = 't')]
+        todo: bool,
+
+        /// Indent the json, to read it rather than pipe it somewhere.
+        #[arg(long)]
+        pretty: bool,
+
+        #[command(flatten)]
+        created: CreatedFilter,
+    }
+
+    impl DumpArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+
+            let json = todos.with_iter(|all| {
+                let todos: Vec<_> = all
+                    .filter(|t| !self.todo || !t.done)
+                    .filter(|t| self.created.matches(t))
+                    .collect();
+                self.to_json(&todos)
+            })??;
+
+            println!("{json}");
+
+            Ok(())
+        }
+
+        fn to_json(&self, todos: &[&Todo]) -> serde_json::Result<String> {
+            if self.pretty {
+                serde_json::to_string_pretty(todos)
+            } else {
+                serde_json::to_string(todos)
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use clap::Parser;
+        use todo::Todo;
+
+        use crate::{Cli, Command};
+
+        fn dump_args(args: &[&str]) -> super::DumpArgs {
+            let cli = Cli::parse_from([&["todo", "dump"], args].concat());
+            match cli.command {
+                Some(Command::Dump(a)) => a,
+                other => panic!("expected dump, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn pretty_only_when_asked() {
+            let todo = Todo::new("1".to_string());
+
+            let minified = dump_args(&[]).to_json(&[&todo]).unwrap();
+            assert!(!minified.contains('\n'));
+            assert!(!minified.contains("  "));
+
+            let pretty = dump_args(&["--pretty"]).to_json(&[&todo]).unwrap();
+            assert!(pretty.contains("\n  {\n    \"id\""), "{pretty}");
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&pretty).unwrap(),
+                serde_json::from_str::<serde_json::Value>(&minified).unwrap()
+            );
+        }
+    }
+}
+
+mod snapshot {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::Context;
+    use clap::Args;
+    use colored::Colorize;
+    use todo::{Backup, ListDiff, Todo, TodoTime, Todos};
+
+    use crate::{import, savefile};
+
+    /// Where snapshots are kept: a `snapshots` directory beside the save file.
+    fn dir() -> anyhow::Result<PathBuf> {
+        let save_file = savefile::path()?;
+        let dir = save_file
+            .parent()
+            .context("the save file isn't in a directory")?
+            .join("snapshots");
+        Ok(dir)
+    }
+
+    /// Save all the todos as a backup named for the time now, returning the file's path.
+    pub fn take(todos: &Todos<impl todo::persist::TodosDatabase>) -> anyhow::Result<PathBuf> {
+        let dir = dir()?;
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+
+        let name = TodoTime::now().format_utc("%Y%m%dT%H%M%S%3fZ");
+        let path = dir.join(name).with_extension("json");
+        let backup = Backup::new(todos.get_all()?);
+        std::fs::write(&path, serde_json::to_string_pretty(&backup)?)
+            .with_context(|| format!("failed to write the snapshot to {}", path.display()))?;
+
+        Ok(path)
+    }
+
+    #[derive(Debug, Args)]
+    pub struct DiffArgs {
+        /// The earlier snapshot: a path, or the name of one in the snapshots directory.
+        before: PathBuf,
+
+        /// The later snapshot, the same way.
+        after: PathBuf,
+    }
+
+    impl DiffArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let before = read(&self.before)?;
+            let after = read(&self.after)?;
+            let diff = ListDiff::between(&before, &after);
+
+            if diff.is_empty() {
+                eprintln!("[INFO] no todos were added, removed or completed");
+                return Ok(());
+            }
+
+            for todo in &diff.added {
+                println!("{} {}", "+".green(), line(todo));
+            }
+            for todo in &diff.removed {
+                println!("{} {}", "-".red(), line(todo));
+            }
+            for todo in &diff.completed {
+                println!("{} {}", "✓".blue(), line(todo));
+            }
+
+            Ok(())
+        }
+    }
+
+    fn line(todo: &Todo) -> String {
+        format!("{} {}", todo.id.short().dimmed(), todo.message)
+    }
+
+    /// Read a snapshot given as a path, or failing that, by its name in the snapshots directory.
+    fn read(snapshot: &Path) -> anyhow::Result<Vec<Todo>> {
+        let path = if snapshot.exists() {
+            snapshot.to_path_buf()
+        } else {
+            dir()?.join(snapshot).with_extension("json")
+        };
+
+        import::read_todos(&path)
+            .with_context(|| format!("failed to read the snapshot {}", snapshot.display()))
+    }
+}
+
+mod lex {
+    use std::path::PathBuf;
+
+    use anyhow::Context;
+    use clap::Args;
+
+    use crate::{config, lang::lexer::Lexer};
+
+    #[derive(Debug, Args)]
+    pub struct LexArgs {
+        /// The .td file to lex.
+        file: PathBuf,
+    }
+
+    impl LexArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let src = std::fs::read_to_string(&self.file)
+                .with_context(|| format!("failed to read {}", self.file.display()))?;
+
+            let cfg = config::load_config_or_default();
+            println!("{}", tokens_json(&src, &cfg)?);
+
+            Ok(())
+        }
+    }
+
+    /// The tokens of `src`, lexed the way the language server would, as pretty json.
+    fn tokens_json(src: &str, cfg: &config::MyndConfig) -> anyhow::Result<String> {
+        let lexer = Lexer::with_keywords(src, &cfg.keywords())
+            .with_block_delimiters(cfg.block_delimiters)
+            .with_tab_width(cfg.tab_width);
+        let tokens: Vec<_> = lexer.collect();
+
+        Ok(serde_json::to_string_pretty(&tokens)?)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use serde_json::{json, Value};
+
+        use crate::config::MyndConfig;
+
+        #[test]
+        fn prints_kinds_and_spans() {
+            let json = super::tokens_json("todo one\ntodo {\n  two\n}", &MyndConfig::default());
+            let tokens: Value = serde_json::from_str(&json.unwrap()).unwrap();
+
+            let kinds: Vec<_> = tokens
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| (t["kind"].as_str().unwrap(), t["text"].as_str().unwrap()))
+                .collect();
+            assert_eq!(
+                kinds,
+                vec![
+                    ("TodoKeyword", "todo"),
+                    ("String", "one"),
+                    ("TodoKeyword", "todo"),
+                    ("MultilineString", "\n  two\n"),
+                ]
+            );
+
+            assert_eq!(
+                tokens[1]["span"],
+                json!({
+                    "start": { "value": 5, "line": 0, "col": 5 },
+                    "end": { "value": 7, "line": 0, "col": 7 },
+                })
+            );
+            assert_eq!(tokens[3]["span"]["end"]["line"], 3);
+        }
+    }
+}
+
+mod check {
+    use std::path::PathBuf;
+
+    use anyhow::{anyhow, Context};
+    use clap::Args;
+
+    use crate::{config, lang};
+
+    #[derive(Debug, Args)]
+    pub struct CheckArgs {
+        /// The .td file to check.
+        file: PathBuf,
+    }
+
+    impl CheckArgs {
+        /// Prints each error as `file:line:col: message`, and fails if there are any.
+        pub fn handle(self) -> anyhow::Result<()> {
+            let src = std::fs::read_to_string(&self.file)
+                .with_context(|| format!("failed to read {}", self.file.display()))?;
+
+            let cfg = config::load_config_or_default();
+            let problems = problems(&src, &cfg);
+
+            for problem in &problems {
+                println!("{}:{problem}", self.file.display());
+            }
+
+            if !problems.is_empty() {
+                return Err(anyhow!(
+                    "found {} error(s) in {}",
+                    problems.len(),
+                    self.file.display()
+                ));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Each parse error in `src`, as `line:col: message`, counting from 1 like editors do.
+    fn problems(src: &str, cfg: &config::MyndConfig) -> Vec<String> {
+        // an empty file has no todos, but nothing wrong in it either
+        if src.trim().is_empty() {
+            return vec![];
+        }
+
+        lang::parse(src, cfg)
+            .items
+            .iter()
+            .filter_map(|item| item.as_ref().err())
+            .map(|err| {
+                let start = err.span().start;
+                format!("{}:{}: {err}", start.line + 1, start.col + 1)
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::config::MyndConfig;
+
+        use super::*;
+
+        #[test]
+        fn reports_where_the_errors_are() {
+            let src = "todo fine\nstray text\ntodo {\n  also fine\n}\n  todo";
+            assert_eq!(
+                problems(src, &MyndConfig::default()),
+                [
+                    "2:1: dangling text; without todo",
+                    "6:6: reached an unexpected end of file",
+                ]
+            );
+
+            let dir = tempfile::tempdir().unwrap();
+            let broken = dir.path().join("broken.td");
+            std::fs::write(&broken, src).unwrap();
+            let err = CheckArgs { file: broken }.handle().unwrap_err();
+            assert!(err.to_string().starts_with("found 2 error(s)"), "{err}");
+
+            let clean = dir.path().join("clean.td");
+            std::fs::write(&clean, "todo fine\n").unwrap();
+            assert!(CheckArgs { file: clean }.handle().is_ok());
+            assert!(problems("\n  \n", &MyndConfig::default()).is_empty());
+        }
+
+        #[test]
+        fn counts_tabs_by_the_configured_width() {
+            let src = "todo fine\n\tstray text";
+            let tab_width = |tab_width| MyndConfig {
+                tab_width,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                problems(src, &tab_width(1)),
+                ["2:2: dangling text; without todo"]
+            );
+            assert_eq!(
+                problems(src, &tab_width(4)),
+                ["2:5: dangling text; without todo"]
+            );
+        }
+    }
+}
+
+/// The [todo.txt](https://github.com/todotxt/todo.txt) format: a todo per line, like
+/// `x 2024-03-05 2024-03-03 file taxes +money @home pri:B` for a done one, or
+/// `(A) 2024-03-01 call the bank` for a pending one. `+project` and `@context` tags are left in
+/// the message as they are.
+mod todotxt {
+    use todo::{Todo, TodoTime};
+
+    const DATE_FORMAT: &str = "%Y-%m-%d";
+
+    /// With priorities, (A) is the most urgent; mynd's are higher the more urgent, so (Z) is 1
+    /// and (A) is 26, and any higher is (A) too.
+    fn priority_from_letter(letter: char) -> Option<u8> {
+        letter.is_ascii_uppercase().then(|| b'Z' - letter as u8 + 1)
+    }
+
+    fn letter_from_priority(priority: u8) -> Option<char> {
+        (priority > 0).then(|| (b'Z' + 1 - priority.min(26)) as char)
+    }
+
+    /// The priority from a leading `(A) `, and the rest.
+    fn take_priority(text: &str) -> (Option<u8>, &str) {
+        let mut chars = text.chars();
+        if let (Some('('), Some(letter), Some(')'), Some(' ')) =
+            (chars.next(), chars.next(), chars.next(), chars.next())
+        {
+            if let Some(priority) = priority_from_letter(letter) {
+                return (Some(priority), chars.as_str().trim_start());
+            }
+        }
+        (None, text)
+    }
+
+    /// The time from a leading `YYYY-MM-DD `, and the rest.
+    fn take_date(text: &str) -> (Option<TodoTime>, &str) {
+        let Some((word, rest)) = text.split_once(' ') else {
+            return (None, text);
+        };
+        let is_date =
+            word.len() == 10 && chrono::NaiveDate::parse_from_str(word, DATE_FORMAT).is_ok();
+        match word.parse().ok().filter(|_| is_date) {
+            Some(time) => (Some(time), rest.trim_start()),
+            None => (None, text),
+        }
+    }
+
+    /// A todo for each line that isn't blank or a `#` comment.
+    pub fn parse(text: &str) -> Vec<Todo> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(parse_line)
+            .collect()
+    }
+
+    fn parse_line(line: &str) -> Todo {
+        let (done, rest) = match line.strip_prefix("x ") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+        let (mut priority, rest) = take_priority(rest);
+        let (first_date, rest) = take_date(rest);
+        // a done one's first date is when it was done, if there's one for when it was made
+        let (completed_at, created_at, rest) = match (done, first_date) {
+            (true, Some(completed_at)) => match take_date(rest) {
+                (Some(created_at), rest) => (Some(completed_at), Some(created_at), rest),
+                (None, _) => (None, Some(completed_at), rest),
+            },
+            (_, created_at) => (None, created_at, rest),
+        };
+
+        // done ones keep their priority as a `pri:A` tag
+        let mut words = vec![];
+        for word in rest.split(' ') {
+            let tagged = word
+                .strip_prefix("pri:")
+                .and_then(|letter| letter.parse().ok())
+                .and_then(priority_from_letter);
+            match tagged {
+                Some(tagged) if priority.is_none() => priority = Some(tagged),
+                _ => words.push(word),
+            }
+        }
+
+        let mut todo = Todo::new(words.join(" "));
+        todo.done = done;
+        todo.priority = priority.unwrap_or_default();
+        if let Some(created_at) = created_at {
+            todo.created_at = created_at.clone();
+            todo.updated_at = completed_at.unwrap_or(created_at);
+        }
+        todo
+    }
+
+    /// A line for each todo. Lines of multiline messages are joined by spaces.
+    pub fn render(todos: &[Todo]) -> String {
+        todos.iter().map(|todo| render_line(todo) + "\n").collect()
+    }
+
+    fn render_line(todo: &Todo) -> String {
+        let message = todo
+            .message
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let created = todo.created_at.format_local(DATE_FORMAT);
+        let letter = letter_from_priority(todo.priority);
+
+        if todo.done {
+            let completed = todo.updated_at.format_local(DATE_FORMAT);
+            let tag = letter.map(|l| format!(" pri:{l}")).unwrap_or_default();
+            format!("x {completed} {created} {message}{tag}")
+        } else {
+            let priority = letter.map(|l| format!("({l}) ")).unwrap_or_default();
+            format!("{priority}{created} {message}")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::{Todo, TodoTime};
+
+        #[test]
+        fn round_trips_todo_txt() {
+            let sample = "\
+(A) 2024-03-01 call the bank +money @phone
+2024-03-02 water the plants @home
+x 2024-03-05 2024-03-03 file taxes +money pri:B
+x 2024-03-04 2024-03-04 sweep
+";
+            let todos = super::parse(sample);
+
+            let fields: Vec<_> = todos
+                .iter()
+                .map(|t| (t.message.as_str(), t.done, t.priority))
+                .collect();
+            assert_eq!(
+                fields,
+                [
+                    ("call the bank +money @phone", false, 26),
+                    ("water the plants @home", false, 0),
+                    ("file taxes +money", true, 25),
+                    ("sweep", true, 0),
+                ]
+            );
+            let date = |s: &str| s.parse::<TodoTime>().unwrap();
+            assert_eq!(todos[2].created_at, date("2024-03-03"));
+            assert_eq!(todos[2].updated_at, date("2024-03-05"));
+
+            assert_eq!(super::render(&todos), sample);
+        }
+
+        #[test]
+        fn reads_loose_lines() {
+            let todos = super::parse("# a comment\n\n  x  2024-13-01 not a date\nplain (B) text\n");
+
+            assert_eq!(todos.len(), 2);
+            assert!(todos[0].done);
+            assert_eq!(todos[0].message, "2024-13-01 not a date");
+            assert_eq!(todos[1].message, "plain (B) text");
+            assert_eq!(todos[1].priority, 0);
+
+            let mut multiline = Todo::new("one\n  two\n".to_string());
+            multiline.priority = 40;
+            let line = super::render(&[multiline]);
+            assert!(line.starts_with("(A) "), "{line}");
+            assert!(line.ends_with(" one two\n"), "{line}");
+        }
+    }
+}
+
+mod share {
+    use std::io::{Read, Write};
+
+    use anyhow::Context;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use clap::Args;
+    use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+    use todo::{Todo, Todos};
+
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+    #[derive(Debug, Args)]
+    pub struct ShareArgs {
+        /// Gzip the todos first, for a shorter string when there are many.
+        #[arg(short, long)]
+        compress: bool,
+    }
+
+    impl ShareArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+            let pending: Vec<_> = todos.get_all()?.into_iter().filter(|t| !t.done).collect();
+
+            println!("{}", encode(&pending, self.compress)?);
+
+            Ok(())
+        }
+    }
+
+    /// The todos as compact json, maybe gzipped, in url-safe base64.
+    pub fn encode(todos: &[Todo], compress: bool) -> anyhow::Result<String> {
+        let mut data = serde_json::to_vec(todos)?;
+        if compress {
+            let mut encoder = GzEncoder::new(vec![], Compression::best());
+            encoder.write_all(&data)?;
+            data = encoder.finish().context("failed to compress the todos")?;
+        }
+
+        Ok(URL_SAFE_NO_PAD.encode(data))
+    }
+
+    /// The todos from an [`encode`]d string, compressed or not.
+    pub fn decode(shared: &str) -> anyhow::Result<Vec<Todo>> {
+        let mut data = URL_SAFE_NO_PAD
+            .decode(shared.trim())
+            .context("the shared todos aren't valid base64")?;
+
+        if data.starts_with(&GZIP_MAGIC) {
+            let mut decompressed = vec![];
+            GzDecoder::new(&data[..])
+                .read_to_end(&mut decompressed)
+                .context("failed to decompress the shared todos")?;
+            data = decompressed;
+        }
+
+        serde_json::from_slice(&data).context("the shared todos aren't valid")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::Todo;
+
+        use super::{decode, encode};
+
+        #[test]
+        fn round_trips_messages_in_order() {
+            let todos: Vec<_> = ["first", "second\nwith more", "third ✓"]
+                .map(|m| Todo::new(m.to_string()))
+                .into_iter()
+                .collect();
+
+            for compress in [false, true] {
+                let shared = encode(&todos, compress).unwrap();
+                assert!(shared
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+                let messages: Vec<_> = decode(&shared)
+                    .unwrap()
+                    .into_iter()
+                    .map(|t| t.message)
+                    .collect();
+                assert_eq!(messages, vec!["first", "second\nwith more", "third ✓"]);
+            }
+
+            assert!(decode("not the todos!").is_err());
+        }
+    }
+}
+
+mod recover {
+    use std::path::PathBuf;
+
+    use clap::Args;
+    use todo::persist::binary;
+
+    #[derive(Debug, Args)]
+    pub struct RecoverArgs {
+        /// The corrupt binary save-file.
+        file: PathBuf,
+
+        /// Where to write the salvaged todos [default: <file>.recovered.bin]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    }
+
+    impl RecoverArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            crate::savefile::ensure_saving("recover")?;
+
+            let output = self
+                .output
+                .unwrap_or_else(|| self.file.with_extension("recovered.bin"));
+
+            let (count, skipped) = binary::recover(&self.file, &output)?;
+
+            for s in &skipped {
+                eprintln!(
+                    "[WARN] skipped {} byte(s) at offset {} that couldn't be read: {}",
+                    s.len, s.offset, s.reason
+                );
+            }
+
+            eprintln!(
+                "[INFO] recovered {} todo(s) into: {}",
+                count,
+                output.display()
+            );
+            eprintln!(
+                "[INFO] bring them back with: todo import {}",
+                output.display()
+            );
+
+            Ok(())
+        }
+    }
+}
+
+mod export {
+    use clap::Args;
+    use todo::{Backup, Todo, Todos};
+
+    #[derive(Debug, Args)]
+    #[group(required = true, multiple = false)]
+    pub struct ExportArgs {
+        /// Export as an iCalendar (.ics) of VTODO entries, e.g. for calendar apps. Todos have
+        /// no due dates, so the entries have no DUE.
+        #[arg(long)]
+        ics: bool,
+
+        /// Export everything as json, along with the version and time of the export, for
+        /// backups that `import` can read back.
+        #[arg(long)]
+        full: bool,
+
+        /// Export in the todo.txt format, which `import` reads back from a .txt file.
+        #[arg(long)]
+        todotxt: bool,
+    }
+
+    impl ExportArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            let todos = Todos::load_up_with_persistor();
+
+            if self.ics {
+                print!("{}", ics(&todos.get_all()?));
+            }
+
+            if self.full {
+                let backup = Backup::new(todos.get_all()?);
+                println!("{}", serde_json::to_string_pretty(&backup)?);
+            }
+
+            if self.todotxt {
+                print!("{}", crate::todotxt::render(&todos.get_all()?));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Render the todos as an RFC 5545 calendar. There's no DUE, since todos have no due date.
+    pub fn ics(todos: &[Todo]) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//Gnarus-G//mynd//EN".to_string(),
+        ];
+
+        for todo in todos {
+            let stamp = todo.created_at.format_utc("%Y%m%dT%H%M%SZ");
+            let status = if todo.done {
+                "COMPLETED"
+            } else {
+                "NEEDS-ACTION"
+            };
+
+            lines.push("BEGIN:VTODO".to_string());
+            lines.push(format!("UID:{}@mynd", todo.id.0));
+            lines.push(format!("DTSTAMP:{stamp}"));
+            lines.push(format!("CREATED:{stamp}"));
+            lines.push(format!("SUMMARY:{}", escape(&todo.message)));
+            lines.push(format!("STATUS:{status}"));
+            lines.push("END:VTODO".to_string());
+        }
+
+        lines.push("END:VCALENDAR".to_string());
+
+        lines.iter().map(|line| fold(line) + "\r\n").collect()
+    }
+
+    fn escape(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+
+        for c in text.chars() {
+            match c {
+                '\\' | ';' | ',' => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '\n' => escaped.push_str("\\n"),
+                '\r' => {}
+                _ => escaped.push(c),
+            }
+        }
+
+        escaped
+    }
+
+    /// Lines longer than 75 octets are split, with each continuation starting with a space.
+    fn fold(line: &str) -> String {
+        let mut folded = String::with_capacity(line.len());
+        let mut octets = 0;
+
+        for c in line.chars() {
+            if octets + c.len_utf8() > 75 {
+                folded.push_str("\r\n ");
+                octets = 1;
+            }
+            octets += c.len_utf8();
+            folded.push(c);
+        }
+
+        folded
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use todo::Todo;
+
+        #[test]
+        fn renders_vtodos() {
+            let pending = Todo::new("call mom, then; dad\\".to_string());
+            let mut done = Todo::new("line one\nline two".to_string());
+            done.done = true;
+
+            let ics = super::ics(&[pending.clone(), done]);
+            let lines: Vec<_> = ics.split("\r\n").collect();
+
+            assert_eq!(ics.matches("BEGIN:VTODO").count(), 2);
+            assert_eq!(ics.matches("END:VTODO").count(), 2);
+            assert!(lines.contains(&"SUMMARY:call mom\\, then\\; dad\\\\"));
+            assert!(lines.contains(&"SUMMARY:line one\\nline two"));
+            assert_eq!(
+                lines
+                    .iter()
+                    .filter(|l| l.starts_with("STATUS:"))
+                    .collect::<Vec<_>>(),
+                vec![&"STATUS:NEEDS-ACTION", &"STATUS:COMPLETED"]
+            );
+            assert!(lines.contains(
+                &format!(
+                    "DTSTAMP:{}",
+                    pending.created_at.format_utc("%Y%m%dT%H%M%SZ")
+                )
+                .as_str()
+            ));
+            assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+            assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        }
+
+        #[test]
+        fn folds_long_lines() {
+            let folded = super::fold(&"x".repeat(100));
+            let parts: Vec<_> = folded.split("\r\n").collect();
+
+            assert_eq!(parts[0].len(), 75);
+            assert_eq!(parts[1], format!(" {}", "x".repeat(25)));
+        }
+    }
+}
+
+mod manageconfigcli {
+    use std::io::stdout;
+
+    use anyhow::Context;
+    use clap::{Args, Subcommand};
+
+    use crate::config::{self, store_config};
+
+    #[derive(Args, Debug, Default)]
+    #[group(required = true, multiple = true)]
+    pub struct ConfigProps {
+        #[arg(short = 'f', long = "format")]
+        /// The storage format of the collection of todo items.
+        storage_format: Option<config::SaveFileFormat>,
+
+        #[arg(long)]
+        /// The editor to open the todo list with.
+        editor: Option<String>,
+
+        #[arg(long)]
+        /// Whether to save binary files by appending changes to a journal.
+        journal: Option<bool>,
+
+        #[arg(long)]
+        /// Whether to gzip the save file.
+        compress: Option<bool>,
+
+        #[arg(long)]
+        /// Whether to remove done todos, for good, after every command that changes the todos.
+        auto_prune_done: Option<bool>,
+
+        #[arg(long)]
+        /// How `ls` orders todos when no `--sort` is given.
+        default_sort: Option<config::DefaultSort>,
+
+        #[arg(long)]
+        /// Whether `done` on a todo that's already done undoes it.
+        done_toggles: Option<bool>,
+
+        #[arg(long)]
+        /// Whether todos can have the same message as others.
+        allow_duplicates: Option<bool>,
+
+        #[arg(long, value_name = "N")]
+        /// Ask before deleting more than this many todos at once.
+        confirm_delete_over: Option<usize>,
+
+        #[arg(long)]
+        /// Whether added todos go at the top or the bottom of the list.
+        insert_at: Option<config::InsertAt>,
+
+        #[arg(long, value_name = "N")]
+        /// How many columns a tab takes up in the positions `check` and `lex` report.
+        tab_width: Option<u32>,
+
+        #[arg(long = "keyword", value_name = "WORD", value_parser = parse_keyword)]
+        /// A word that starts a todo; repeat it for more than one. The first is used when
+        /// writing todos out.
+        keywords: Vec<String>,
+
+        #[arg(long)]
+        /// Whether multiline todos keep the relative indentation of their lines.
+        preserve_indent: Option<bool>,
+
+        #[arg(long, value_name = "COLOR")]
+        /// The color of pending todos in `ls`, e.g. "blue" or "bright red".
+        theme_pending: Option<String>,
+
+        #[arg(long, value_name = "COLOR")]
+        /// The color of done todos in `ls`.
+        theme_done: Option<String>,
+
+        #[arg(long, value_name = "COLOR")]
+        /// The color of ids in `ls`.
+        theme_id: Option<String>,
+
+        #[arg(long, value_name = "COLOR")]
+        /// The color of times in `ls`.
+        theme_time: Option<String>,
+
+        #[arg(long)]
+        /// Whether `todo { ... }` makes a multiline todo.
+        braces: Option<bool>,
+
+        #[arg(long)]
+        /// Whether `todo <<END ... END` makes a multiline todo.
+        heredoc: Option<bool>,
+    }
+
+    impl ConfigProps {
+        /// Update only the fields that were given.
+        fn apply_to(self, cfg: &mut config::MyndConfig) {
+            if let Some(format) = self.storage_format {
+                cfg.save_file_format = format;
+            }
+            if let Some(editor) = self.editor {
+                cfg.editor = Some(editor);
+            }
+            if let Some(journal) = self.journal {
+                cfg.journal = journal;
+            }
+            if let Some(compress) = self.compress {
+                cfg.compress = compress;
+            }
+            if let Some(auto_prune_done) = self.auto_prune_done {
+                cfg.auto_prune_done = auto_prune_done;
+            }
+            if let Some(default_sort) = self.default_sort {
+                cfg.default_sort = default_sort;
+            }
+            if let Some(done_toggles) = self.done_toggles {
+                cfg.done_toggles = done_toggles;
+            }
+            if let Some(allow_duplicates) = self.allow_duplicates {
+                cfg.allow_duplicates = allow_duplicates;
+            }
+            if let Some(confirm_delete_over) = self.confirm_delete_over {
+                cfg.confirm_delete_over = confirm_delete_over;
+            }
+            if let Some(insert_at) = self.insert_at {
+                cfg.insert_at = insert_at;
+            }
+            if let Some(tab_width) = self.tab_width {
+                cfg.tab_width = tab_width;
+            }
+            if !self.keywords.is_empty() {
+                cfg.keywords = self.keywords;
+            }
+            if let Some(preserve_indent) = self.preserve_indent {
+                cfg.preserve_indent = preserve_indent;
+            }
+            if let Some(pending) = self.theme_pending {
+                cfg.theme.pending = Some(pending);
+            }
+            if let Some(done) = self.theme_done {
+                cfg.theme.done = Some(done);
+            }
+            if let Some(id) = self.theme_id {
+                cfg.theme.id = Some(id);
+            }
+            if let Some(time) = self.theme_time {
+                cfg.theme.time = Some(time);
+            }
+            if let Some(braces) = self.braces {
+                cfg.block_delimiters.braces = braces;
+            }
+            if let Some(heredoc) = self.heredoc {
+                cfg.block_delimiters.heredoc = heredoc;
+            }
+        }
+    }
+
+    /// Keywords are lexed as a letter then letters and underscores, so nothing else can be one.
+    fn parse_keyword(word: &str) -> anyhow::Result<String> {
+        let starts_with_letter = word.starts_with(|c: char| c.is_ascii_alphabetic());
+        if !starts_with_letter || !word.chars().all(|c| c.is_ascii_alphabetic() || c == '_') {
+            return Err(anyhow::anyhow!(
+                "a keyword can only be a letter then letters and underscores"
+            ));
+        }
+        Ok(word.to_string())
+    }
+
+    #[derive(Subcommand, Debug)]
+    pub enum ConfigActions {
+        /// Update configuration values.
+        Set(ConfigProps),
+        /// Print configuration values to standard output as json.
+        Show,
+        /// Check that the config file can be read, erroring if it's corrupt.
+        Check,
+    }
+
+    #[derive(Args, Debug)]
+    pub struct ConfigArgs {
+        #[command(subcommand)]
+        command: ConfigActions,
+    }
+
+    impl ConfigArgs {
+        pub fn handle(self) -> anyhow::Result<()> {
+            match self.command {
+                ConfigActions::Set(props) => {
+                    let mut cfg = config::load_config()?;
+                    props.apply_to(&mut cfg);
+                    store_config(cfg)?;
+                }
+                ConfigActions::Show => {
+                    let cfg = config::load_config()?;
+                    serde_json::to_writer_pretty(stdout(), &cfg)?;
+                    println!()
+                }
+                ConfigActions::Check => {
+                    let path = config::config_path()?;
+                    config::load_config()
+                        .with_context(|| format!("the config at {} is corrupt", path.display()))?;
+                    eprintln!("[INFO] the config at {} is fine", path.display());
+                }
+            };
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn setting_one_field_keeps_the_others() {
+            let mut cfg = config::MyndConfig {
+                save_file_format: config::SaveFileFormat::Json,
+                editor: Some("nano".to_string()),
+                ..Default::default()
+            };
+
+            ConfigProps {
+                editor: Some("nvim".to_string()),
+                ..Default::default()
+            }
+            .apply_to(&mut cfg);
+
+            assert!(matches!(cfg.save_file_format, config::SaveFileFormat::Json));
+            assert_eq!(cfg.editor.as_deref(), Some("nvim"));
+
+            ConfigProps {
+                storage_format: Some(config::SaveFileFormat::Binary),
+                ..Default::default()
+            }
+            .apply_to(&mut cfg);
+
+            assert!(matches!(
+                cfg.save_file_format,
+                config::SaveFileFormat::Binary
+            ));
+            assert_eq!(cfg.editor.as_deref(), Some("nvim"));
+
+            ConfigProps {
+                keywords: vec!["task".to_string(), "fixme".to_string()],
+                ..Default::default()
+            }
+            .apply_to(&mut cfg);
+            assert_eq!(cfg.keywords(), ["task", "fixme"]);
+            assert_eq!(cfg.editor.as_deref(), Some("nvim"));
+            assert!(parse_keyword("fix-me").is_err());
+            assert!(parse_keyword("_fixme").is_err());
+
+            ConfigProps {
+                preserve_indent: Some(true),
+                ..Default::default()
+            }
+            .apply_to(&mut cfg);
+            assert!(cfg.preserve_indent);
+            assert_eq!(cfg.keywords(), ["task", "fixme"]);
+
+            ConfigProps {
+                theme_done: Some("bright black".to_string()),
+                ..Default::default()
+            }
+            .apply_to(&mut cfg);
+            ConfigProps {
+                theme_id: Some("blue".to_string()),
+                ..Default::default()
+            }
+            .apply_to(&mut cfg);
+            assert_eq!(cfg.theme.done.as_deref(), Some("bright black"));
+            assert_eq!(cfg.theme.id.as_deref(), Some("blue"));
+            assert_eq!(cfg.theme.pending, None);
+
+            ConfigProps {
+                heredoc: Some(true),
+                ..Default::default()
+            }
+            .apply_to(&mut cfg);
+            assert_eq!(
+                cfg.block_delimiters,
+                config::BlockDelimiters {
+                    braces: true,
+                    heredoc: true
+                }
+            );
         }
     }
 }
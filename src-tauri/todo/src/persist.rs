@@ -1,8 +1,64 @@
-use std::path::{Path, PathBuf};
+use std::{
+    ffi::OsString,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
 use anyhow::Context;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use tracing::{debug, warn};
+
+use crate::{
+    config::{load_config_or_default, SaveFileFormat},
+    Todo,
+};
+
+/// Why the save file couldn't be read or written, so callers can tell failures apart.
+/// These come wrapped in `anyhow` errors, see [`PersistError::of`].
+#[derive(thiserror::Error, Debug)]
+pub enum PersistError {
+    #[error("failed to access {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("the save file is corrupt: {0}")]
+    Deserialize(String),
+    #[error("failed to resolve the save file: {0}")]
+    Config(String),
+}
+
+impl PersistError {
+    /// Find the persistence failure behind an error, if that's what caused it.
+    pub fn of(err: &anyhow::Error) -> Option<&PersistError> {
+        err.chain().find_map(|e| e.downcast_ref::<PersistError>())
+    }
+
+    fn io(path: &Path) -> impl FnOnce(std::io::Error) -> Self + '_ {
+        move |source| Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
 
-use crate::{config::load_config, Todo};
+    fn deserialize(err: impl std::fmt::Display) -> Self {
+        Self::Deserialize(format!("{err:#}"))
+    }
+
+    /// For errors held on to and reported again on every use.
+    fn duplicate(&self) -> Self {
+        match self {
+            Self::Io { path, source } => Self::Io {
+                path: path.clone(),
+                source: std::io::Error::new(source.kind(), source.to_string()),
+            },
+            Self::Deserialize(message) => Self::Deserialize(message.clone()),
+            Self::Config(message) => Self::Config(message.clone()),
+        }
+    }
+}
 
 pub trait TodosDatabase {
     fn get_all_todos(&self) -> anyhow::Result<Vec<Todo>>;
@@ -13,23 +69,87 @@ pub trait TodosDatabase {
 pub enum ActualTodosDB {
     JsonFile(jsonfile::TodosJsonDB),
     BinaryFile(binary::TodosBin),
+    /// For [`IN_MEMORY_VAR`]; gone with the process.
+    InMemory(crate::inmem::MemoryDB),
+}
+
+/// Set to `1` to keep the todos in memory instead of the save file, e.g. for tests and demos of
+/// the cli. Every process starts with no todos, and nothing it changes is saved.
+pub const IN_MEMORY_VAR: &str = "MYND_INMEMORY";
+
+/// Whether [`IN_MEMORY_VAR`] is set, so the save file isn't to be used at all.
+pub fn in_memory() -> bool {
+    is_in_memory(std::env::var_os(IN_MEMORY_VAR))
+}
+
+fn is_in_memory(value: Option<OsString>) -> bool {
+    value.is_some_and(|v| v == "1")
 }
 
 impl Default for ActualTodosDB {
     fn default() -> Self {
-        let cfg = load_config().unwrap_or_default();
+        Self::from_env(
+            std::env::var_os(IN_MEMORY_VAR),
+            std::env::var_os("MYND_DATA_DIR"),
+            std::env::var_os("HOME"),
+        )
+    }
+}
+
+impl ActualTodosDB {
+    /// The store to use, given the values of [`IN_MEMORY_VAR`], `$MYND_DATA_DIR` and `$HOME`.
+    fn from_env(
+        in_memory: Option<OsString>,
+        data_dir: Option<OsString>,
+        home: Option<OsString>,
+    ) -> Self {
+        if is_in_memory(in_memory) {
+            debug!("keeping the todos in memory, because of ${IN_MEMORY_VAR}");
+            return Self::InMemory(Default::default());
+        }
+
+        if let Some(path) = SAVE_FILE_OVERRIDE.get() {
+            debug!("using the save file at {}", path.display());
+            return Self::JsonFile(jsonfile::TodosJsonDB::at(path.clone()));
+        }
+
+        let cfg = load_config_or_default();
+        let dir = data_dir_from(data_dir, home);
+
+        if let Ok(dir) = &dir {
+            // there's a Todos for every command, and more than one in some
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            if let Some(warning) = unused_save_file_warning(dir, &cfg.save_file_format) {
+                WARNED.call_once(|| warn!("{warning}"));
+            }
+        }
 
         return match cfg.save_file_format {
-            crate::config::SaveFileFormat::Json => {
-                eprintln!("[INFO] using 'json' save file because of configuration.");
-                Self::JsonFile(jsonfile::TodosJsonDB::default())
+            SaveFileFormat::Json => {
+                debug!("using 'json' save file because of configuration");
+                Self::JsonFile(jsonfile::TodosJsonDB::in_dir(dir).with_compression(cfg.compress))
             }
-            crate::config::SaveFileFormat::Binary => {
-                eprintln!("[INFO] using 'binary' save file because of configuration.");
-                Self::BinaryFile(binary::TodosBin::default())
+            SaveFileFormat::Binary => {
+                debug!("using 'binary' save file because of configuration");
+                Self::BinaryFile(
+                    binary::TodosBin::in_dir(dir)
+                        .with_journal(cfg.journal)
+                        .with_compression(cfg.compress),
+                )
             }
         };
     }
+
+    /// The changes saved to the journal since it was last compacted, oldest first.
+    pub fn journal_entries(&self) -> anyhow::Result<Vec<binary::journal::Entry>> {
+        match self {
+            ActualTodosDB::JsonFile(_) => Err(anyhow::anyhow!(
+                "only binary save files keep a journal, see `config set --format binary --journal true`"
+            )),
+            ActualTodosDB::BinaryFile(db) => db.journal_entries(),
+            ActualTodosDB::InMemory(_) => Ok(vec![]),
+        }
+    }
 }
 
 impl TodosDatabase for ActualTodosDB {
@@ -37,6 +157,7 @@ impl TodosDatabase for ActualTodosDB {
         match self {
             ActualTodosDB::JsonFile(db) => db.get_all_todos(),
             ActualTodosDB::BinaryFile(db) => db.get_all_todos(),
+            ActualTodosDB::InMemory(db) => db.get_all_todos(),
         }
     }
 
@@ -44,43 +165,70 @@ impl TodosDatabase for ActualTodosDB {
         match self {
             ActualTodosDB::JsonFile(db) => db.set_all_todos(todos),
             ActualTodosDB::BinaryFile(db) => db.set_all_todos(todos),
+            ActualTodosDB::InMemory(db) => db.set_all_todos(todos),
         }
     }
 }
 
 pub mod jsonfile {
-    use super::{get_or_create_savefilename, TodosDatabase};
+    use super::{
+        decompress_if_gzipped, get_or_create_savefilename, gzip, keep_previous, savefilename_in,
+        PersistError, TodosDatabase, JSON_SAVE_FILE,
+    };
 
     use std::{
         fs::{File, OpenOptions},
-        io::{BufReader, Write},
+        io::{Read, Write},
         path::{Path, PathBuf},
     };
 
-    use anyhow::{anyhow, Context};
+    use anyhow::Context;
     use serde::{de::DeserializeOwned, Serialize};
 
     #[derive(Debug)]
     pub struct TodosJsonDB {
-        filename: anyhow::Result<PathBuf>,
+        filename: Result<PathBuf, PersistError>,
+        compressed: bool,
     }
 
     impl Default for TodosJsonDB {
         fn default() -> Self {
             Self {
-                filename: get_or_create_savefilename("todo.json"),
+                filename: get_or_create_savefilename(JSON_SAVE_FILE),
+                compressed: false,
             }
         }
     }
 
     impl TodosJsonDB {
+        /// Uses the save file in the given data directory.
+        pub(super) fn in_dir(dir: Result<PathBuf, PersistError>) -> Self {
+            Self {
+                filename: savefilename_in(dir, JSON_SAVE_FILE),
+                compressed: false,
+            }
+        }
+
+        /// Uses the file at the given path, instead of the one in the data directory.
+        pub fn at(filename: PathBuf) -> Self {
+            Self {
+                filename: Ok(filename),
+                compressed: false,
+            }
+        }
+
+        /// Gzip the file when saving. Reading handles both, either way.
+        pub fn with_compression(mut self, compressed: bool) -> Self {
+            self.compressed = compressed;
+            self
+        }
+
         fn get_filename(&self) -> anyhow::Result<&Path> {
             match &self.filename {
                 Ok(p) => Ok(p),
                 Err(err) => {
-                    return Err(
-                        anyhow!("{:#}", err).context("failed to setup a json file for the todos")
-                    );
+                    return Err(anyhow::Error::new(err.duplicate())
+                        .context("failed to setup a json file for the todos"));
                 }
             }
         }
@@ -89,39 +237,93 @@ pub mod jsonfile {
     impl TodosDatabase for TodosJsonDB {
         fn get_all_todos(&self) -> anyhow::Result<Vec<crate::Todo>> {
             let json_file_name = self.get_filename()?;
-            read_json(json_file_name)
+
+            // a new save-file has no todos yet; same as the binary one
+            let is_new = std::fs::metadata(json_file_name)
+                .map(|m| m.len() == 0)
+                .unwrap_or(true);
+            if is_new {
+                return Ok(vec![]);
+            }
+
+            read_save_file(json_file_name)
         }
 
         fn set_all_todos(&self, todos: Vec<crate::Todo>) -> anyhow::Result<()> {
             let json_file_name = self.get_filename()?;
+            keep_previous(json_file_name).context("failed to keep the previous save file")?;
+
+            if self.compressed {
+                let json = serde_json::to_vec(&todos)?;
+                std::fs::write(json_file_name, gzip(&json)?)
+                    .map_err(PersistError::io(json_file_name))
+                    .context("failed to write compressed json file")?;
+                return Ok(());
+            }
+
             write_json(json_file_name, todos)?;
             Ok(())
         }
     }
 
+    /// Reads json, or gzipped json, from the file.
     pub fn read_json<Item: DeserializeOwned + Serialize>(filename: &Path) -> anyhow::Result<Item> {
-        let p =
-            &std::env::var("HOME").context("failed to resolve the HOME environment variable")?;
-        let file = open_file(&Path::new(p).join(filename))?;
-        let reader = BufReader::new(&file);
-        let item = serde_json::from_reader(reader).context("failed to read json data")?;
+        let data = read_data(filename)?;
+        let item = serde_json::from_slice(&data)
+            .map_err(PersistError::deserialize)
+            .context("failed to read json data")?;
         Ok(item)
     }
 
+    /// Reads the todos saved in the file. A lone todo, say from a hand edit that lost the
+    /// brackets around the list, is taken as a list of one.
+    fn read_save_file(filename: &Path) -> anyhow::Result<Vec<crate::Todo>> {
+        let data = read_data(filename)?;
+
+        let err = match serde_json::from_slice(&data) {
+            Ok(todos) => return Ok(todos),
+            Err(err) => err,
+        };
+
+        if let Ok(todo) = serde_json::from_slice::<crate::Todo>(&data) {
+            tracing::warn!(
+                "{} has a single todo instead of a list of them, reading it as a list of one",
+                filename.display()
+            );
+            return Ok(vec![todo]);
+        }
+
+        Err(PersistError::Deserialize(format!(
+            "{err} in {}; it should be a json array of todos",
+            filename.display()
+        )))
+        .context("failed to read json data")
+    }
+
+    fn read_data(filename: &Path) -> anyhow::Result<Vec<u8>> {
+        let mut file = open_file(filename)?;
+        let mut data = vec![];
+        file.read_to_end(&mut data)
+            .map_err(PersistError::io(filename))
+            .context("failed to read from json file")?;
+        Ok(decompress_if_gzipped(data)?)
+    }
+
     pub fn write_json<Item: DeserializeOwned + Serialize>(
         filename: &Path,
         item: Item,
     ) -> anyhow::Result<()> {
         let json = serde_json::to_string::<Item>(&item)?;
-        let p = &std::env::var("HOME")?;
 
         let mut file = OpenOptions::new()
             .write(true)
             .read(true)
+            .create(true)
             .truncate(true)
-            .open(Path::new(p).join(filename))?;
+            .open(filename)
+            .map_err(PersistError::io(filename))?;
 
-        write!(file, "{}", json)?;
+        write!(file, "{}", json).map_err(PersistError::io(filename))?;
         Ok(())
     }
 
@@ -132,15 +334,121 @@ pub mod jsonfile {
             .create(true)
             .truncate(false)
             .open(path)
+            .map_err(PersistError::io(path))
             .context("failed to open json file for reading/writing")?;
 
         Ok(file)
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::TodosJsonDB;
+        use crate::{
+            persist::{find_local_save_file, prev_path, revert, TodosDatabase},
+            Todo,
+        };
+
+        #[test]
+        fn test_finds_the_nearest_project_save_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let project = dir.path().join("project");
+            let nested = project.join("src").join("deep");
+            std::fs::create_dir_all(project.join(".mynd")).unwrap();
+            std::fs::create_dir_all(&nested).unwrap();
+
+            let expected = project.join(".mynd").join("todo.json");
+            assert_eq!(find_local_save_file(&nested), Some(expected.clone()));
+            assert_eq!(find_local_save_file(&project), Some(expected));
+
+            let inner = nested.join(".mynd");
+            std::fs::create_dir(&inner).unwrap();
+            assert_eq!(
+                find_local_save_file(&nested),
+                Some(inner.join("todo.json")),
+                "the nearest one wins"
+            );
+        }
+
+        #[test]
+        fn test_revert_to_the_previous_save() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.json");
+            let db = TodosJsonDB::at(filename.clone());
+            let first = vec![Todo::new("one".to_string())];
+            let second = vec![Todo::new("two".to_string())];
+
+            db.set_all_todos(first.clone()).unwrap();
+            let first_content = std::fs::read(&filename).unwrap();
+            assert!(!prev_path(&filename).exists());
+
+            db.set_all_todos(second.clone()).unwrap();
+            assert_eq!(std::fs::read(prev_path(&filename)).unwrap(), first_content);
+
+            revert(&filename).unwrap();
+            assert_eq!(db.get_all_todos().unwrap(), first);
+            revert(&filename).unwrap();
+            assert_eq!(db.get_all_todos().unwrap(), second);
+        }
+
+        #[test]
+        fn test_single_todo_is_read_as_a_list() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.json");
+            let todo = Todo::new("one".to_string());
+            std::fs::write(&filename, serde_json::to_vec_pretty(&todo).unwrap()).unwrap();
+
+            let db = TodosJsonDB::at(filename);
+            assert_eq!(db.get_all_todos().unwrap(), vec![todo]);
+        }
+
+        #[test]
+        fn test_malformed_json_says_where() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.json");
+            let todo = serde_json::to_string_pretty(&Todo::new("one".to_string())).unwrap();
+            std::fs::write(&filename, format!("[\n{todo},\n]")).unwrap();
+
+            let err = TodosJsonDB::at(filename.clone())
+                .get_all_todos()
+                .unwrap_err();
+            let message = format!("{err:#}");
+
+            let lines = todo.lines().count() as u32;
+            assert!(
+                message.contains(&format!("trailing comma at line {}", lines + 2)),
+                "{message}"
+            );
+            assert!(
+                message.contains(&filename.display().to_string()),
+                "{message}"
+            );
+            assert!(crate::persist::PersistError::of(&err).is_some());
+        }
+
+        #[test]
+        fn test_compressed_json_save_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.json");
+            let todos = vec![Todo::new("one".to_string())];
+
+            let db = TodosJsonDB::at(filename.clone()).with_compression(true);
+            db.set_all_todos(todos.clone()).unwrap();
+
+            assert_eq!(std::fs::read(&filename).unwrap()[..2], [0x1f, 0x8b]);
+            assert_eq!(db.get_all_todos().unwrap(), todos);
+
+            std::fs::write(&filename, serde_json::to_vec(&todos).unwrap()).unwrap();
+            assert_eq!(db.get_all_todos().unwrap(), todos);
+        }
+    }
 }
 
 pub mod binary {
 
-    use std::{fs::OpenOptions, io::Read};
+    use std::{
+        fs::OpenOptions,
+        io::{Read, Write},
+    };
 
     use anyhow::{anyhow, Context};
     use chrono::DateTime;
@@ -149,6 +457,64 @@ pub mod binary {
 
     use super::*;
 
+    /// Version of the per-todo record format written by [`Todo::to_binary`].
+    /// - 1: message, timestamp, and done flag.
+    /// - 2: adds the order.
+    /// - 3: adds the pinned flag.
+    /// - 4: adds the last updated timestamp.
+    /// - 5: adds the priority byte.
+    /// - 6: adds the byte saying the id is per creation time (allow_duplicates).
+    const VERSION: u8 = 6;
+
+    /// The fewest bytes a todo record can take: version, message length, timestamp and done flag,
+    /// with an empty message.
+    const MIN_RECORD_LEN: usize = 14;
+
+    /// Ends a save-file that has a checksum, followed by the CRC32 of everything before it.
+    /// Files saved before checksums end with their last record instead.
+    const CHECKSUM_MAGIC: &[u8; 4] = b"MYCK";
+    const CHECKSUM_FOOTER_LEN: usize = CHECKSUM_MAGIC.len() + 4;
+
+    fn with_checksum(mut data: Vec<u8>) -> Vec<u8> {
+        let checksum = crc32fast::hash(&data);
+        data.extend_from_slice(CHECKSUM_MAGIC);
+        data.extend_from_slice(&checksum.to_be_bytes());
+        data
+    }
+
+    /// The records of a save-file, and the checksum it was saved with if it has one.
+    pub fn strip_checksum(data: &[u8]) -> (&[u8], Option<u32>) {
+        let Some(split) = data.len().checked_sub(CHECKSUM_FOOTER_LEN) else {
+            return (data, None);
+        };
+
+        let (records, footer) = data.split_at(split);
+        let (magic, checksum) = footer.split_at(CHECKSUM_MAGIC.len());
+        if magic != CHECKSUM_MAGIC {
+            return (data, None);
+        }
+
+        let checksum = u32::from_be_bytes(checksum.try_into().expect("4 bytes left"));
+        (records, Some(checksum))
+    }
+
+    /// The records of a save-file, failing if they don't match the checksum it was saved with.
+    fn verified_records(data: &[u8]) -> Result<&[u8], PersistError> {
+        let (records, checksum) = strip_checksum(data);
+
+        if let Some(expected) = checksum {
+            let actual = crc32fast::hash(records);
+            if actual != expected {
+                return Err(PersistError::Deserialize(format!(
+                    "its checksum doesn't match (saved {expected:08x}, now {actual:08x}); \
+                     try `recover` to salvage what todos can be read"
+                )));
+            }
+        }
+
+        Ok(records)
+    }
+
     fn into_int_bytes(int: usize) -> [u8; 4] {
         let int = int as u32;
         return int.to_be_bytes();
@@ -165,14 +531,29 @@ pub mod binary {
                 .expect("failed to get timestamp nanos, not in range?");
             let time_bin = timestamp.to_be_bytes();
             let done_bin: u8 = if self.done { 1 } else { 0 };
+            let order_bin = self.order.to_be_bytes();
+            let pinned_bin: u8 = if self.pinned { 1 } else { 0 };
+            let updated_bin = self
+                .updated_at
+                .0
+                .timestamp_nanos_opt()
+                .expect("failed to get timestamp nanos, not in range?")
+                .to_be_bytes();
+            let priority_bin = self.priority;
+            let distinct_bin = (self.id != TodoID::hash_message(&self.message)) as u8;
 
-            let version: &[u8] = &[1];
+            let version: &[u8] = &[VERSION];
             let data = [
                 version,                             // first byte is the version of this format
                 &into_int_bytes(self.message.len()), // next 4 bytes is message len
                 message_bin,                         // next len bytes is message
                 &time_bin,                           // next 8 bytes in timestamp
-                &[done_bin],                         // last byte is 0 or 1 for isDone flag
+                &[done_bin],                         // next byte is 0 or 1 for isDone flag
+                &order_bin,                          // next 4 bytes is the order
+                &[pinned_bin],                       // next byte is 0 or 1 for isPinned flag
+                &updated_bin,                        // next 8 bytes is the updated timestamp
+                &[priority_bin],                     // next byte is the priority
+                &[distinct_bin],                     // last byte is 1 if the id's per creation time
             ]
             .concat();
 
@@ -181,7 +562,11 @@ pub mod binary {
 
         /// Expecting data to be a reverse byte buffer, so as to emulate a stack.
         fn from_binary(data: &mut Vec<u8>) -> anyhow::Result<Todo> {
-            let _version_byte = data.pop().context("empty data")?;
+            let version_byte = data.pop().context("empty data")?;
+
+            if !(1..=VERSION).contains(&version_byte) {
+                return Err(anyhow!("unsupported todo record version: {}", version_byte));
+            }
 
             let mut message_len = [0u8; 4];
             for i in message_len.iter_mut() {
@@ -190,6 +575,13 @@ pub mod binary {
 
             let message_len = u32::from_be_bytes(message_len);
 
+            if message_len as usize > data.len() {
+                return Err(anyhow!(
+                    "message length {} overruns the remaining data",
+                    message_len
+                ));
+            }
+
             let mut message = Vec::with_capacity(message_len as usize);
             for _ in 0..message_len {
                 let byte = data.pop().context("empty data")?;
@@ -210,67 +602,234 @@ pub mod binary {
                 .context("empty data")
                 .context("failed to read done byte")?;
 
+            if is_done_byte > 1 {
+                return Err(anyhow!("invalid done byte: {}", is_done_byte));
+            }
+
+            let mut order = [0u8; 4];
+            if version_byte >= 2 {
+                for i in order.iter_mut() {
+                    *i = data
+                        .pop()
+                        .context("empty data")
+                        .context("failed to read order")?
+                }
+            }
+
+            let mut is_pinned_byte = 0;
+            if version_byte >= 3 {
+                is_pinned_byte = data
+                    .pop()
+                    .context("empty data")
+                    .context("failed to read pinned byte")?;
+
+                if is_pinned_byte > 1 {
+                    return Err(anyhow!("invalid pinned byte: {}", is_pinned_byte));
+                }
+            }
+
+            let mut updated_at = todo_time.clone();
+            if version_byte >= 4 {
+                let mut updated_nanos = [0u8; 8];
+                for i in updated_nanos.iter_mut() {
+                    *i = data
+                        .pop()
+                        .context("empty data")
+                        .context("failed to read updated timestamp")?
+                }
+                updated_at = crate::TodoTime(DateTime::from_timestamp_nanos(i64::from_be_bytes(
+                    updated_nanos,
+                )));
+            }
+
+            let mut priority = 0;
+            if version_byte >= 5 {
+                priority = data
+                    .pop()
+                    .context("empty data")
+                    .context("failed to read priority")?;
+            }
+
+            let mut id = TodoID::hash_message(&message);
+            if version_byte >= 6 {
+                let distinct_byte = data
+                    .pop()
+                    .context("empty data")
+                    .context("failed to read distinct id byte")?;
+                if distinct_byte == 1 {
+                    id = TodoID::hash_message_at(&message, &todo_time);
+                }
+            }
+
             Ok(Self {
-                id: TodoID::hash_message(&message),
+                id,
                 message,
                 created_at: todo_time,
-                done: is_done_byte != 0,
+                done: is_done_byte == 1,
+                order: u32::from_be_bytes(order),
+                pinned: is_pinned_byte == 1,
+                updated_at,
+                priority,
             })
         }
     }
 
     #[derive(Debug)]
     pub struct TodosBin {
-        filename: anyhow::Result<PathBuf>,
+        filename: Result<PathBuf, PersistError>,
+        /// Whether to save by appending changes to the journal, instead of rewriting the file.
+        journaled: bool,
+        compressed: bool,
     }
 
     impl Default for TodosBin {
         fn default() -> Self {
             Self {
-                filename: get_or_create_savefilename("todo.bin"),
+                filename: get_or_create_savefilename(BINARY_SAVE_FILE),
+                journaled: false,
+                compressed: false,
             }
         }
     }
 
+    /// How many changes the journal may hold before they're compacted into the save-file.
+    const COMPACT_AFTER: usize = 256;
+
     impl TodosBin {
+        pub(super) fn at(filename: PathBuf) -> Self {
+            Self {
+                filename: Ok(filename),
+                journaled: false,
+                compressed: false,
+            }
+        }
+
+        /// Uses the save-file in the given data directory.
+        pub(super) fn in_dir(dir: Result<PathBuf, PersistError>) -> Self {
+            Self {
+                filename: savefilename_in(dir, BINARY_SAVE_FILE),
+                journaled: false,
+                compressed: false,
+            }
+        }
+
+        pub fn with_journal(mut self, journaled: bool) -> Self {
+            self.journaled = journaled;
+            self
+        }
+
+        /// Gzip the file when saving. Reading handles both, either way.
+        pub fn with_compression(mut self, compressed: bool) -> Self {
+            self.compressed = compressed;
+            self
+        }
+
         fn get_filename(&self) -> anyhow::Result<&Path> {
             match &self.filename {
                 Ok(p) => Ok(p),
                 Err(err) => {
-                    return Err(
-                        anyhow!("{:#}", err).context("failed to setup a binary file for the todos")
-                    );
+                    return Err(anyhow::Error::new(err.duplicate())
+                        .context("failed to setup a binary file for the todos"));
                 }
             }
         }
-    }
 
-    impl TodosDatabase for TodosBin {
-        fn get_all_todos(&self) -> anyhow::Result<Vec<Todo>> {
+        fn journal(&self) -> anyhow::Result<journal::Journal> {
+            let path = with_suffix(self.get_filename()?, ".journal");
+            Ok(journal::Journal::new(path))
+        }
+
+        fn read_file(&self) -> anyhow::Result<Vec<Todo>> {
             let filename = self.get_filename()?;
             let mut file = OpenOptions::new()
                 .read(true)
                 .create(true)
                 .append(true)
-                .open(filename)?;
+                .open(filename)
+                .map_err(PersistError::io(filename))?;
 
             let mut data = vec![];
 
             file.read_to_end(&mut data)
+                .map_err(PersistError::io(filename))
                 .context("failed to read binary save-file of todos")?;
 
-            get_todos_from_binary(&mut data)
+            let data = decompress_if_gzipped(data)?;
+            let mut records = verified_records(&data)?.to_vec();
+
+            let todos = get_todos_from_binary(&mut records).map_err(PersistError::deserialize)?;
+
+            Ok(todos)
         }
 
-        fn set_all_todos(&self, todos: Vec<Todo>) -> anyhow::Result<()> {
+        /// Rewrite the save-file, keeping the one it replaces.
+        fn write_file(&self, todos: &[Todo]) -> anyhow::Result<()> {
             let filename = self.get_filename()?;
-            let data = convert_todos_to_binary(&todos);
-            std::fs::write(filename, data).context(anyhow!(
-                "failed to write to todos binary save-file: {}",
-                filename.display()
-            ))?;
+            keep_previous(filename).context("failed to keep the previous save file")?;
+
+            let mut data = with_checksum(convert_todos_to_binary(todos));
+            if self.compressed {
+                data = gzip(&data)?;
+            }
+            std::fs::write(filename, data)
+                .map_err(PersistError::io(filename))
+                .context(anyhow!(
+                    "failed to write to todos binary save-file: {}",
+                    filename.display()
+                ))?;
             Ok(())
         }
+
+        /// The changes in the journal, oldest first.
+        pub fn journal_entries(&self) -> anyhow::Result<Vec<journal::Entry>> {
+            self.journal()?.read()
+        }
+
+        /// Fold the journal into the save-file.
+        pub fn compact(&self) -> anyhow::Result<()> {
+            let todos = self.get_all_todos()?;
+            let journal = self.journal()?;
+            keep_previous(journal.path()).context("failed to keep the previous journal")?;
+            self.write_file(&todos)?;
+            journal.clear()
+        }
+    }
+
+    impl TodosDatabase for TodosBin {
+        fn get_all_todos(&self) -> anyhow::Result<Vec<Todo>> {
+            let todos = self.read_file()?;
+            let entries = self.journal()?.read()?;
+
+            Ok(journal::replay(
+                todos,
+                entries.into_iter().map(|e| e.change),
+            ))
+        }
+
+        fn set_all_todos(&self, todos: Vec<Todo>) -> anyhow::Result<()> {
+            let journal = self.journal()?;
+            keep_previous(journal.path()).context("failed to keep the previous journal")?;
+
+            if !self.journaled {
+                self.write_file(&todos)?;
+                return journal.clear();
+            }
+
+            let entries = journal.read()?;
+            let entry_count = entries.len();
+            let current = journal::replay(self.read_file()?, entries.into_iter().map(|e| e.change));
+            let changes = journal::diff(&current, &todos);
+
+            if entry_count + changes.len() > COMPACT_AFTER {
+                self.write_file(&todos)?;
+                return journal.clear();
+            }
+
+            // only the journal changes, so the save-file is its own previous one
+            keep_unchanged(self.get_filename()?)
+                .context("failed to keep the previous save file")?;
+            journal.append(changes)
+        }
     }
 
     pub fn get_todos_from_binary(data: &mut Vec<u8>) -> anyhow::Result<Vec<Todo>> {
@@ -278,25 +837,451 @@ pub mod binary {
             return Ok(vec![]);
         }
 
-        let mut todos = Vec::with_capacity(data.len() / 14); // 14 is the intended minimum of bytes
-                                                             // to represent a todo message, (i.e an
-                                                             // empty message string)
+        let mut todos = Vec::with_capacity(data.len() / MIN_RECORD_LEN);
         data.reverse(); // make it a stack.
         while !data.is_empty() {
-            let t = Todo::from_binary(data)?;
-            todos.push(t)
+            let remaining = data.len();
+
+            match Todo::from_binary(data) {
+                Ok(t) => todos.push(t),
+                // A record cut short at the end, e.g. by a crash mid-write, shouldn't cost the
+                // todos before it.
+                Err(err)
+                    if !todos.is_empty() && (data.is_empty() || remaining < MIN_RECORD_LEN) =>
+                {
+                    warn!(
+                        "ignoring {} trailing bytes in the binary save-file: {:#}",
+                        remaining, err
+                    );
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
         }
 
-        debug_assert!(!todos.is_empty());
-
         return Ok(todos);
     }
 
+    /// A run of bytes that couldn't be decoded as todo records.
+    #[derive(Debug, PartialEq)]
+    pub struct SkippedBytes {
+        pub offset: usize,
+        pub len: usize,
+        pub reason: String,
+    }
+
+    /// Decode every todo record that can be decoded, skipping over (and reporting) any bytes
+    /// that can't, e.g. records from an unknown version of the format.
+    pub fn get_todos_from_binary_lossy(data: &[u8]) -> (Vec<Todo>, Vec<SkippedBytes>) {
+        let mut todos = vec![];
+        let mut skipped: Vec<SkippedBytes> = vec![];
+        let mut skipping: Option<SkippedBytes> = None;
+
+        let mut stack = data.iter().rev().copied().collect::<Vec<_>>();
+
+        while !stack.is_empty() {
+            let offset = data.len() - stack.len();
+
+            match Todo::from_binary(&mut stack) {
+                Ok(todo) => {
+                    skipped.extend(skipping.take());
+                    todos.push(todo);
+                }
+                Err(err) => {
+                    // put back what the failed attempt consumed, minus the first byte, and try
+                    // again from the next offset.
+                    let consumed_to = data.len() - stack.len();
+                    stack.extend(data[offset + 1..consumed_to].iter().rev());
+
+                    match &mut skipping {
+                        Some(run) => run.len += 1,
+                        None => {
+                            skipping = Some(SkippedBytes {
+                                offset,
+                                len: 1,
+                                reason: format!("{err:#}"),
+                            })
+                        }
+                    }
+                }
+            }
+        }
+
+        skipped.extend(skipping);
+
+        (todos, skipped)
+    }
+
+    /// Salvage what todos can be read from a corrupt binary save-file into a new file at `to`.
+    /// Returns how many todos were salvaged, and what had to be skipped.
+    pub fn recover(from: &Path, to: &Path) -> anyhow::Result<(usize, Vec<SkippedBytes>)> {
+        let data = std::fs::read(from).map_err(PersistError::io(from))?;
+        let data = decompress_if_gzipped(data)?;
+
+        // a checksum that doesn't match is likely why recovering, so it's not checked
+        let (records, _) = strip_checksum(&data);
+        let (todos, skipped) = get_todos_from_binary_lossy(records);
+
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(to)
+            .and_then(|mut file| file.write_all(&with_checksum(convert_todos_to_binary(&todos))))
+            .map_err(PersistError::io(to))
+            .context("failed to write the recovered todos")?;
+
+        Ok((todos.len(), skipped))
+    }
+
     fn convert_todos_to_binary(todos: &[Todo]) -> Vec<u8> {
         let data = todos.iter().flat_map(|t| t.to_binary()).collect::<Vec<_>>();
         return data;
     }
 
+    /// An append-only log of changes to the todos, kept next to the binary save-file so a save
+    /// doesn't have to rewrite the whole file.
+    pub mod journal {
+        use std::{
+            collections::{HashMap, HashSet},
+            fs::OpenOptions,
+            io::Write,
+            path::{Path, PathBuf},
+        };
+
+        use anyhow::{anyhow, Context};
+        use chrono::DateTime;
+
+        use crate::{Todo, TodoID, TodoTime};
+
+        use super::into_int_bytes;
+
+        const ADD: u8 = 0;
+        const DONE: u8 = 1;
+        const REMOVE: u8 = 2;
+        const MOVE: u8 = 3;
+        const PIN: u8 = 4;
+        const TOUCH: u8 = 5;
+        const PRIORITY: u8 = 6;
+
+        /// One change to the list of todos.
+        #[derive(Debug, Clone)]
+        #[cfg_attr(test, derive(PartialEq))]
+        pub enum Change {
+            Add(Todo),
+            Done { id: TodoID, done: bool },
+            Remove { id: TodoID },
+            Move { id: TodoID, order: u32 },
+            Pin { id: TodoID, pinned: bool },
+            Touch { id: TodoID, updated_at: TodoTime },
+            Priority { id: TodoID, priority: u8 },
+        }
+
+        impl std::fmt::Display for Change {
+            /// Like `done 1a2b3c4`, with short ids, and the message for added ones.
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Change::Add(todo) => write!(f, "add {} {:?}", todo.id.short(), todo.message),
+                    Change::Done { id, done: true } => write!(f, "done {}", id.short()),
+                    Change::Done { id, done: false } => write!(f, "undone {}", id.short()),
+                    Change::Remove { id } => write!(f, "remove {}", id.short()),
+                    Change::Move { id, order } => write!(f, "move {} to {}", id.short(), order),
+                    Change::Pin { id, pinned: true } => write!(f, "pin {}", id.short()),
+                    Change::Pin { id, pinned: false } => write!(f, "unpin {}", id.short()),
+                    Change::Touch { id, .. } => write!(f, "touch {}", id.short()),
+                    Change::Priority { id, priority } => {
+                        write!(f, "prioritize {} to {}", id.short(), priority)
+                    }
+                }
+            }
+        }
+
+        #[derive(Debug, Clone)]
+        #[cfg_attr(test, derive(PartialEq))]
+        pub struct Entry {
+            pub at: TodoTime,
+            pub change: Change,
+        }
+
+        impl Entry {
+            fn to_binary(&self) -> Vec<u8> {
+                let timestamp = self
+                    .at
+                    .0
+                    .timestamp_nanos_opt()
+                    .expect("failed to get timestamp nanos, not in range?");
+
+                let (kind, payload) = match &self.change {
+                    Change::Add(todo) => (ADD, todo.to_binary()),
+                    Change::Done { id, done } => {
+                        (DONE, [id_to_binary(id), vec![*done as u8]].concat())
+                    }
+                    Change::Remove { id } => (REMOVE, id_to_binary(id)),
+                    Change::Move { id, order } => (
+                        MOVE,
+                        [id_to_binary(id), order.to_be_bytes().to_vec()].concat(),
+                    ),
+                    Change::Pin { id, pinned } => {
+                        (PIN, [id_to_binary(id), vec![*pinned as u8]].concat())
+                    }
+                    Change::Touch { id, updated_at } => {
+                        let nanos = updated_at
+                            .0
+                            .timestamp_nanos_opt()
+                            .expect("failed to get timestamp nanos, not in range?");
+                        (
+                            TOUCH,
+                            [id_to_binary(id), nanos.to_be_bytes().to_vec()].concat(),
+                        )
+                    }
+                    Change::Priority { id, priority } => {
+                        (PRIORITY, [id_to_binary(id), vec![*priority]].concat())
+                    }
+                };
+
+                [&[kind][..], &timestamp.to_be_bytes(), &payload].concat()
+            }
+
+            /// Expecting data to be a reverse byte buffer, so as to emulate a stack.
+            fn from_binary(data: &mut Vec<u8>) -> anyhow::Result<Entry> {
+                let [kind] = pop_bytes(data)?;
+                let at = TodoTime(DateTime::from_timestamp_nanos(i64::from_be_bytes(
+                    pop_bytes(data)?,
+                )));
+
+                let change = match kind {
+                    ADD => Change::Add(Todo::from_binary(data)?),
+                    DONE => Change::Done {
+                        id: pop_id(data)?,
+                        done: pop_bytes::<1>(data)? == [1],
+                    },
+                    REMOVE => Change::Remove { id: pop_id(data)? },
+                    MOVE => Change::Move {
+                        id: pop_id(data)?,
+                        order: u32::from_be_bytes(pop_bytes(data)?),
+                    },
+                    PIN => Change::Pin {
+                        id: pop_id(data)?,
+                        pinned: pop_bytes::<1>(data)? == [1],
+                    },
+                    TOUCH => Change::Touch {
+                        id: pop_id(data)?,
+                        updated_at: TodoTime(DateTime::from_timestamp_nanos(i64::from_be_bytes(
+                            pop_bytes(data)?,
+                        ))),
+                    },
+                    PRIORITY => Change::Priority {
+                        id: pop_id(data)?,
+                        priority: pop_bytes::<1>(data)?[0],
+                    },
+                    kind => return Err(anyhow!("unknown journal entry kind: {}", kind)),
+                };
+
+                Ok(Entry { at, change })
+            }
+        }
+
+        fn pop_bytes<const N: usize>(data: &mut Vec<u8>) -> anyhow::Result<[u8; N]> {
+            let mut bytes = [0u8; N];
+            for b in bytes.iter_mut() {
+                *b = data.pop().context("empty data")?;
+            }
+            Ok(bytes)
+        }
+
+        fn id_to_binary(id: &TodoID) -> Vec<u8> {
+            [&into_int_bytes(id.0.len())[..], id.0.as_bytes()].concat()
+        }
+
+        fn pop_id(data: &mut Vec<u8>) -> anyhow::Result<TodoID> {
+            let len = u32::from_be_bytes(pop_bytes(data)?) as usize;
+            if len > data.len() {
+                return Err(anyhow!("id length {} overruns the remaining data", len));
+            }
+
+            let id = data.split_off(data.len() - len).into_iter().rev().collect();
+            Ok(String::from_utf8(id).context("id was not in utf-8")?.into())
+        }
+
+        /// The changes that turn `old` into `new`.
+        pub fn diff(old: &[Todo], new: &[Todo]) -> Vec<Change> {
+            let old_by_id: HashMap<_, _> = old.iter().map(|t| (&t.id, t)).collect();
+            let new_ids: HashSet<_> = new.iter().map(|t| &t.id).collect();
+
+            let mut changes: Vec<_> = old
+                .iter()
+                .filter(|t| !new_ids.contains(&t.id))
+                .map(|t| Change::Remove { id: t.id.clone() })
+                .collect();
+
+            for todo in new {
+                let Some(before) = old_by_id.get(&todo.id) else {
+                    changes.push(Change::Add(todo.clone()));
+                    continue;
+                };
+
+                if before.done != todo.done {
+                    changes.push(Change::Done {
+                        id: todo.id.clone(),
+                        done: todo.done,
+                    });
+                }
+
+                if before.order != todo.order {
+                    changes.push(Change::Move {
+                        id: todo.id.clone(),
+                        order: todo.order,
+                    });
+                }
+
+                if before.pinned != todo.pinned {
+                    changes.push(Change::Pin {
+                        id: todo.id.clone(),
+                        pinned: todo.pinned,
+                    });
+                }
+
+                if before.priority != todo.priority {
+                    changes.push(Change::Priority {
+                        id: todo.id.clone(),
+                        priority: todo.priority,
+                    });
+                }
+
+                if before.updated_at != todo.updated_at {
+                    changes.push(Change::Touch {
+                        id: todo.id.clone(),
+                        updated_at: todo.updated_at.clone(),
+                    });
+                }
+            }
+
+            changes
+        }
+
+        /// Apply the changes, in sequence, on top of the todos.
+        pub fn replay(
+            mut todos: Vec<Todo>,
+            changes: impl IntoIterator<Item = Change>,
+        ) -> Vec<Todo> {
+            for change in changes {
+                match change {
+                    Change::Add(todo) => {
+                        if !todos.iter().any(|t| t.id == todo.id) {
+                            todos.push(todo);
+                        }
+                    }
+                    Change::Done { id, done } => {
+                        if let Some(todo) = todos.iter_mut().find(|t| t.id == id) {
+                            todo.done = done;
+                        }
+                    }
+                    Change::Remove { id } => todos.retain(|t| t.id != id),
+                    Change::Move { id, order } => {
+                        if let Some(todo) = todos.iter_mut().find(|t| t.id == id) {
+                            todo.order = order;
+                        }
+                    }
+                    Change::Pin { id, pinned } => {
+                        if let Some(todo) = todos.iter_mut().find(|t| t.id == id) {
+                            todo.pinned = pinned;
+                        }
+                    }
+                    Change::Touch { id, updated_at } => {
+                        if let Some(todo) = todos.iter_mut().find(|t| t.id == id) {
+                            todo.updated_at = updated_at;
+                        }
+                    }
+                    Change::Priority { id, priority } => {
+                        if let Some(todo) = todos.iter_mut().find(|t| t.id == id) {
+                            todo.priority = priority;
+                        }
+                    }
+                }
+            }
+
+            todos.sort_by_key(|t| t.order);
+            todos
+        }
+
+        #[derive(Debug)]
+        pub struct Journal {
+            path: PathBuf,
+        }
+
+        impl Journal {
+            pub fn new(path: PathBuf) -> Self {
+                Self { path }
+            }
+
+            pub fn path(&self) -> &Path {
+                &self.path
+            }
+
+            pub fn read(&self) -> anyhow::Result<Vec<Entry>> {
+                let mut data = match std::fs::read(&self.path) {
+                    Ok(data) => data,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+                    Err(err) => return Err(err).context("failed to read the todos journal"),
+                };
+
+                let mut entries = vec![];
+
+                data.reverse(); // make it a stack.
+                while !data.is_empty() {
+                    match Entry::from_binary(&mut data) {
+                        Ok(entry) => entries.push(entry),
+                        Err(err) => {
+                            // most likely an append that was cut short, so nothing after it.
+                            tracing::warn!("ignoring the unreadable end of the journal: {err:#}");
+                            break;
+                        }
+                    }
+                }
+
+                Ok(entries)
+            }
+
+            pub fn append(&self, changes: Vec<Change>) -> anyhow::Result<()> {
+                if changes.is_empty() {
+                    return Ok(());
+                }
+
+                let at = TodoTime::now();
+                let data = changes
+                    .into_iter()
+                    .flat_map(|change| {
+                        Entry {
+                            at: at.clone(),
+                            change,
+                        }
+                        .to_binary()
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)
+                    .context("failed to open the todos journal")?;
+
+                file.write_all(&data)
+                    .context("failed to append to the todos journal")?;
+                file.sync_data()?;
+
+                Ok(())
+            }
+
+            pub fn clear(&self) -> anyhow::Result<()> {
+                match std::fs::remove_file(&self.path) {
+                    Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                        Err(err).context("failed to clear the todos journal")
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -315,6 +1300,18 @@ pub mod binary {
                 if self.done != other.done {
                     return false;
                 }
+                if self.order != other.order {
+                    return false;
+                }
+                if self.pinned != other.pinned {
+                    return false;
+                }
+                if self.updated_at != other.updated_at {
+                    return false;
+                }
+                if self.priority != other.priority {
+                    return false;
+                }
 
                 return true;
             }
@@ -343,26 +1340,772 @@ pub mod binary {
 
             assert!(data.is_empty())
         }
+
+        /// Deterministic stand-in for random input, so a failure can be run again.
+        struct XorShift(u64);
+
+        impl XorShift {
+            fn next(&mut self) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+            }
+
+            fn below(&mut self, n: u64) -> u64 {
+                self.next() % n
+            }
+
+            fn time(&mut self) -> crate::TodoTime {
+                // nanoseconds since the epoch, anywhere up to about 2262
+                let nanos = self.below(i64::MAX as u64) as i64;
+                crate::TodoTime(DateTime::from_timestamp_nanos(nanos))
+            }
+
+            fn todo(&mut self) -> Todo {
+                const CHARS: &[char] = &['a', 'z', ' ', '\n', '{', 'é', 'ß', '日', '🧀', '\0'];
+                let len = self.below(300);
+                let message: String = (0..len)
+                    .map(|_| CHARS[self.below(CHARS.len() as u64) as usize])
+                    .collect();
+                let created_at = self.time();
+                let id = if self.below(2) == 0 {
+                    TodoID::hash_message(&message)
+                } else {
+                    TodoID::hash_message_at(&message, &created_at)
+                };
+
+                Todo {
+                    id,
+                    message,
+                    created_at,
+                    done: self.below(2) == 0,
+                    order: self.next() as u32,
+                    pinned: self.below(2) == 0,
+                    updated_at: self.time(),
+                    priority: self.next() as u8,
+                }
+            }
+        }
+
+        #[test]
+        fn test_serde_binary_keeps_the_order_of_random_lists() {
+            let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+            for case in 0..200 {
+                let len = rng.below(40) as usize;
+                let todos: Vec<_> = (0..len).map(|_| rng.todo()).collect();
+
+                let mut data = convert_todos_to_binary(&todos);
+                let decoded = get_todos_from_binary(&mut data).unwrap();
+
+                assert_eq!(decoded.len(), todos.len(), "case {case}");
+                for (i, (decoded, todo)) in decoded.iter().zip(&todos).enumerate() {
+                    assert_eq!(decoded, todo, "case {case}, todo {i}");
+                }
+                assert!(data.is_empty(), "case {case}");
+            }
+        }
+
+        #[test]
+        fn test_serde_binary_version_1() {
+            let t = Todo::new("old".to_string());
+            let mut data = t.to_binary();
+            data[0] = 1;
+            data.truncate(data.len() - 15); // v1 had only the message, created time and done flag
+
+            data.reverse();
+            assert_eq!(t, Todo::from_binary(&mut data).unwrap());
+            assert!(data.is_empty())
+        }
+
+        #[test]
+        fn test_serde_binary_distinct_id() {
+            let mut t = Todo::new("same".to_string());
+            t.id = TodoID::hash_message_at(&t.message, &t.created_at);
+
+            let mut data = t.to_binary();
+            data.reverse();
+            assert_eq!(t, Todo::from_binary(&mut data).unwrap());
+        }
+
+        #[test]
+        fn test_serde_binary_pinned() {
+            let t = Todo {
+                pinned: true,
+                ..Todo::new("pinned".to_string())
+            };
+            let mut data = t.to_binary();
+            data.reverse();
+            assert_eq!(t, Todo::from_binary(&mut data).unwrap());
+
+            let mut v2 = t.to_binary();
+            v2[0] = 2;
+            v2.truncate(v2.len() - 11);
+            v2.reverse();
+            assert!(!Todo::from_binary(&mut v2).unwrap().pinned);
+        }
+
+        fn reordered(todos: &[&Todo]) -> Vec<Todo> {
+            todos
+                .iter()
+                .enumerate()
+                .map(|(order, t)| Todo {
+                    order: order as u32,
+                    ..(*t).clone()
+                })
+                .collect()
+        }
+
+        #[test]
+        fn test_journal_replays_changes() {
+            let dir = tempfile::tempdir().unwrap();
+            let journal = journal::Journal::new(dir.path().join("todo.bin.journal"));
+
+            let [one, two, three, four] =
+                ["one", "two", "three", "four"].map(|m| Todo::new(m.to_string()));
+            let before = reordered(&[&one, &two, &three]);
+            let mut after = reordered(&[&three, &one, &four]);
+            after[1].done = true;
+            after[1].updated_at = "2200-01-01T00:00:00Z".parse().unwrap();
+            after[0].pinned = true;
+            after[0].priority = 3;
+
+            journal.append(journal::diff(&before, &after)).unwrap();
+
+            let entries = journal.read().unwrap();
+            // remove two, add four, done/move/touch one, and move/pin/prioritize three
+            assert_eq!(entries.len(), 8);
+
+            let replayed = journal::replay(before, entries.into_iter().map(|e| e.change));
+            assert_eq!(replayed, after);
+        }
+
+        #[test]
+        fn test_journal_reads_as_operations() {
+            let dir = tempfile::tempdir().unwrap();
+            let journal = journal::Journal::new(dir.path().join("todo.bin.journal"));
+
+            let todo = Todo::new("water the plants".to_string());
+            let id = todo.id.clone();
+            journal
+                .append(vec![
+                    journal::Change::Add(todo),
+                    journal::Change::Done {
+                        id: id.clone(),
+                        done: true,
+                    },
+                    journal::Change::Move {
+                        id: id.clone(),
+                        order: 2,
+                    },
+                ])
+                .unwrap();
+            journal
+                .append(vec![
+                    journal::Change::Done {
+                        id: id.clone(),
+                        done: false,
+                    },
+                    journal::Change::Remove { id: id.clone() },
+                ])
+                .unwrap();
+
+            let entries = journal.read().unwrap();
+            let short = id.short();
+            assert_eq!(
+                entries
+                    .iter()
+                    .map(|e| e.change.to_string())
+                    .collect::<Vec<_>>(),
+                [
+                    format!("add {short} \"water the plants\""),
+                    format!("done {short}"),
+                    format!("move {short} to 2"),
+                    format!("undone {short}"),
+                    format!("remove {short}"),
+                ]
+            );
+            assert!(entries.windows(2).all(|pair| pair[0].at <= pair[1].at));
+        }
+
+        #[test]
+        fn test_revert_a_journaled_save() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.bin");
+            let db = TodosBin::at(filename.clone()).with_journal(true);
+
+            let [one, two] = ["one", "two"].map(|m| Todo::new(m.to_string()));
+            let first = reordered(&[&one]);
+            db.set_all_todos(first.clone()).unwrap();
+            let second = reordered(&[&one, &two]);
+            db.set_all_todos(second.clone()).unwrap();
+
+            use std::os::unix::fs::MetadataExt;
+            let inode = |path: &Path| std::fs::metadata(path).unwrap().ino();
+            assert_eq!(
+                inode(&filename),
+                inode(&prev_path(&filename)),
+                "appending to the journal doesn't copy the save-file"
+            );
+
+            revert(&filename).unwrap();
+            assert_eq!(db.get_all_todos().unwrap(), first);
+            revert(&filename).unwrap();
+            assert_eq!(db.get_all_todos().unwrap(), second);
+
+            // enough changes at once to fold the journal into the save-file
+            let many: Vec<_> = (0..COMPACT_AFTER)
+                .map(|i| Todo::new(i.to_string()))
+                .collect();
+            let third = reordered(&many.iter().collect::<Vec<_>>());
+            db.set_all_todos(third.clone()).unwrap();
+            assert!(db.journal().unwrap().read().unwrap().is_empty());
+            assert_eq!(db.get_all_todos().unwrap(), third);
+
+            revert(&filename).unwrap();
+            assert_eq!(db.get_all_todos().unwrap(), second);
+            revert(&filename).unwrap();
+            assert_eq!(db.get_all_todos().unwrap(), third);
+
+            db.compact().unwrap();
+            revert(&filename).unwrap();
+            assert_eq!(
+                db.get_all_todos().unwrap(),
+                third,
+                "compacting changes no todos"
+            );
+        }
+
+        #[test]
+        fn test_journal_compacts_into_save_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.bin");
+            let db = TodosBin::at(filename.clone()).with_journal(true);
+
+            let [one, two, three] = ["one", "two", "three"].map(|m| Todo::new(m.to_string()));
+            db.set_all_todos(reordered(&[&one, &two])).unwrap();
+            let mut latest = reordered(&[&two, &three]);
+            latest[0].done = true;
+            db.set_all_todos(latest.clone()).unwrap();
+
+            assert!(std::fs::read(&filename).unwrap().is_empty());
+            assert_eq!(db.get_all_todos().unwrap(), latest);
+
+            db.compact().unwrap();
+
+            assert_eq!(
+                std::fs::read(&filename).unwrap(),
+                with_checksum(convert_todos_to_binary(&latest))
+            );
+            assert!(db.journal().unwrap().read().unwrap().is_empty());
+            assert_eq!(db.get_all_todos().unwrap(), latest);
+        }
+
+        #[test]
+        fn test_compressed_save_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.bin");
+            let todos = vec![Todo::new("one".to_string()), Todo::new("two".to_string())];
+
+            let db = TodosBin::at(filename.clone()).with_compression(true);
+            db.set_all_todos(todos.clone()).unwrap();
+
+            assert_eq!(std::fs::read(&filename).unwrap()[..2], [0x1f, 0x8b]);
+            assert_eq!(db.get_all_todos().unwrap(), todos);
+
+            // and it stays readable when compression is turned off
+            assert_eq!(TodosBin::at(filename).get_all_todos().unwrap(), todos);
+        }
+
+        #[test]
+        fn test_reads_uncompressed_save_file_when_compressing() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.bin");
+            let todos = vec![Todo::new("one".to_string())];
+
+            std::fs::write(&filename, convert_todos_to_binary(&todos)).unwrap();
+
+            let db = TodosBin::at(filename).with_compression(true);
+            assert_eq!(db.get_all_todos().unwrap(), todos);
+        }
+
+        #[test]
+        fn test_error_kinds() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.bin");
+            std::fs::write(&filename, [VERSION, 0, 0, 0, 9, b'x']).unwrap();
+
+            let err = TodosBin::at(filename).get_all_todos().unwrap_err();
+            assert!(matches!(
+                PersistError::of(&err),
+                Some(PersistError::Deserialize(_))
+            ));
+
+            let homeless = TodosBin {
+                filename: savefilename_in(data_dir_from(None, None), "todo.bin"),
+                journaled: false,
+                compressed: false,
+            };
+            let err = homeless.get_all_todos().unwrap_err();
+            assert!(matches!(
+                PersistError::of(&err),
+                Some(PersistError::Config(_))
+            ));
+            assert!(format!("{err:#}").contains("MYND_DATA_DIR"));
+
+            let err = TodosBin::at(dir.path().join("missing").join("todo.bin"))
+                .get_all_todos()
+                .unwrap_err();
+            assert!(matches!(
+                PersistError::of(&err),
+                Some(PersistError::Io { .. })
+            ));
+        }
+
+        #[test]
+        fn test_checksum_round_trip() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.bin");
+            let todos = reordered(&[&Todo::new("one".to_string()), &Todo::new("two".to_string())]);
+            let db = TodosBin::at(filename.clone());
+
+            db.set_all_todos(todos.clone()).unwrap();
+
+            let data = std::fs::read(&filename).unwrap();
+            let (records, checksum) = strip_checksum(&data);
+            assert_eq!(records, convert_todos_to_binary(&todos));
+            assert_eq!(checksum, Some(crc32fast::hash(records)));
+            assert_eq!(db.get_all_todos().unwrap(), todos);
+
+            db.set_all_todos(vec![]).unwrap();
+            assert!(db.get_all_todos().unwrap().is_empty());
+        }
+
+        #[test]
+        fn test_checksum_catches_a_flipped_byte() {
+            let dir = tempfile::tempdir().unwrap();
+            let filename = dir.path().join("todo.bin");
+            let todos = vec![Todo::new("one".to_string())];
+            let db = TodosBin::at(filename.clone());
+            db.set_all_todos(todos.clone()).unwrap();
+
+            let mut data = std::fs::read(&filename).unwrap();
+            data[6] ^= 0x01; // in the message
+            std::fs::write(&filename, &data).unwrap();
+
+            let err = db.get_all_todos().unwrap_err();
+            assert!(matches!(
+                PersistError::of(&err),
+                Some(PersistError::Deserialize(_))
+            ));
+            assert!(
+                format!("{err:#}").contains("checksum doesn't match"),
+                "{err:#}"
+            );
+
+            // and files from before checksums still read
+            std::fs::write(&filename, convert_todos_to_binary(&todos)).unwrap();
+            assert_eq!(db.get_all_todos().unwrap(), todos);
+        }
+
+        #[test]
+        fn test_recover_truncated_save_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let (from, to) = (dir.path().join("todo.bin"), dir.path().join("saved.bin"));
+            let todos = vec![
+                Todo::new("one".to_string()),
+                Todo::new("two".to_string()),
+                Todo::new("three".to_string()),
+            ];
+
+            let mut data = convert_todos_to_binary(&todos);
+            data.truncate(data.len() - 6);
+            std::fs::write(&from, &data).unwrap();
+
+            let (count, skipped) = recover(&from, &to).unwrap();
+
+            assert_eq!(count, 2);
+            assert_eq!(skipped.len(), 1);
+            assert_eq!(
+                skipped[0].offset,
+                convert_todos_to_binary(&todos[..2]).len()
+            );
+            assert_eq!(
+                TodosBin::at(to.clone()).get_all_todos().unwrap(),
+                todos[..2]
+            );
+
+            assert!(recover(&from, &to).is_err(), "doesn't overwrite");
+        }
+
+        #[test]
+        fn test_data_dir_resolution() {
+            let dir = |data_dir: Option<&str>, home: Option<&str>| {
+                data_dir_from(data_dir.map(Into::into), home.map(Into::into))
+                    .map_err(|err| err.to_string())
+            };
+
+            assert_eq!(dir(None, Some("/home/me")), Ok("/home/me/mynd".into()));
+            assert_eq!(dir(Some("/data"), Some("/home/me")), Ok("/data".into()));
+            assert_eq!(dir(Some("/data"), None), Ok("/data".into()));
+            assert_eq!(dir(Some(""), Some("/home/me")), Ok("/home/me/mynd".into()));
+
+            let homeless = dir(None, None).unwrap_err();
+            assert_eq!(dir(None, Some("")).unwrap_err(), homeless);
+            assert!(homeless.contains("$HOME isn't set"));
+            assert!(homeless.contains("MYND_DATA_DIR"));
+        }
+
+        #[test]
+        fn test_save_file_path_per_format() {
+            let dir = tempfile::tempdir().unwrap();
+            let data_dir = dir.path().join("nested").join("mynd");
+
+            let json = save_file_path_in(Ok(data_dir.clone()), &SaveFileFormat::Json).unwrap();
+            assert_eq!(json, data_dir.join("todo.json"));
+            assert!(data_dir.is_dir());
+
+            let bin = save_file_path_in(Ok(data_dir.clone()), &SaveFileFormat::Binary).unwrap();
+            assert_eq!(bin, data_dir.join("todo.bin"));
+        }
+
+        #[test]
+        fn test_in_memory_store_saves_nowhere() {
+            let db = ActualTodosDB::from_env(Some("1".into()), None, None);
+            assert!(matches!(db, ActualTodosDB::InMemory(_)));
+
+            let todos = crate::Todos::new(db);
+            todos.add_message("ephemeral").unwrap();
+            todos.flush().unwrap();
+            assert_eq!(todos.db.get_all_todos().unwrap().len(), 1);
+
+            let next_run = ActualTodosDB::from_env(Some("1".into()), None, None);
+            assert!(next_run.get_all_todos().unwrap().is_empty());
+        }
+
+        #[test]
+        fn test_in_memory_store_leaves_the_save_file_alone() {
+            let dir = tempfile::tempdir().unwrap();
+            let save = |in_memory: Option<&str>| {
+                let db = ActualTodosDB::from_env(
+                    in_memory.map(OsString::from),
+                    Some(dir.path().into()),
+                    None,
+                );
+                let todos = crate::Todos::new(db);
+                todos.add_message("ephemeral").unwrap();
+                todos.flush().unwrap();
+            };
+
+            save(Some("1"));
+            assert!(!dir.path().join(BINARY_SAVE_FILE).exists());
+            assert!(!dir.path().join(JSON_SAVE_FILE).exists());
+            assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+            save(None);
+            assert_ne!(
+                std::fs::read_dir(dir.path()).unwrap().count(),
+                0,
+                "without it, the todos are saved in the data directory"
+            );
+        }
+
+        #[test]
+        fn test_warns_of_todos_in_the_other_format() {
+            let dir = tempfile::tempdir().unwrap();
+            let todos = vec![Todo::new("one".to_string()), Todo::new("two".to_string())];
+            TodosBin::at(dir.path().join("todo.bin"))
+                .set_all_todos(todos.clone())
+                .unwrap();
+
+            assert_eq!(
+                unused_save_file_warning(dir.path(), &SaveFileFormat::Binary),
+                None,
+                "only the configured one is there"
+            );
+
+            let json = dir.path().join("todo.json");
+            std::fs::write(&json, "[]").unwrap();
+            assert_eq!(
+                unused_save_file_warning(dir.path(), &SaveFileFormat::Binary),
+                None,
+                "the other one has no todos"
+            );
+
+            jsonfile::TodosJsonDB::at(json.clone())
+                .set_all_todos(todos)
+                .unwrap();
+            let warning = unused_save_file_warning(dir.path(), &SaveFileFormat::Binary).unwrap();
+            assert!(warning.contains(&format!("{} has 2 todos", json.display())));
+            assert!(warning.contains("mynd config set -f json"));
+
+            let warning = unused_save_file_warning(dir.path(), &SaveFileFormat::Json).unwrap();
+            assert!(warning.contains("todo.bin has 2 todos"));
+            assert!(warning.contains("mynd config set -f binary"));
+        }
+
+        #[test]
+        fn test_binary_ignores_trailing_garbage() {
+            let todos = [Todo::new("one".to_string()), Todo::new("two".to_string())];
+
+            let mut data = [convert_todos_to_binary(&todos), vec![VERSION, 0, 0]].concat();
+            assert_eq!(get_todos_from_binary(&mut data).unwrap(), todos);
+
+            let cut_short = convert_todos_to_binary(&todos);
+            let mut data = cut_short[..cut_short.len() - 3].to_vec();
+            assert_eq!(get_todos_from_binary(&mut data).unwrap(), todos[..1]);
+        }
+
+        #[test]
+        fn test_lossy_binary_skips_unknown_version() {
+            let first = Todo::new("one".to_string());
+            let last = Todo::new("two".to_string());
+
+            let future_record = [
+                &[9u8][..],          // an unknown version
+                &into_int_bytes(6),  // message len
+                b"future",           // message
+                &[0; 8],             // timestamp
+                &[0],                // done
+                &[0xff, 0xff, 0xff], // some field we don't know about
+            ]
+            .concat();
+
+            let data = [first.to_binary(), future_record.clone(), last.to_binary()].concat();
+
+            let (todos, skipped) = get_todos_from_binary_lossy(&data);
+
+            assert_eq!(todos, vec![first.clone(), last]);
+            assert_eq!(skipped.len(), 1);
+            assert_eq!(skipped[0].offset, first.to_binary().len());
+            assert_eq!(skipped[0].len, future_record.len());
+            assert!(skipped[0]
+                .reason
+                .contains("unsupported todo record version: 9"));
+
+            let mut strict = data.clone();
+            assert!(get_todos_from_binary(&mut strict).is_err());
+        }
     }
 }
 
-fn get_or_create_savefilename(filename: &str) -> anyhow::Result<PathBuf> {
+/// `path` with `suffix` on the end of its file name, e.g. `todo.bin` and `.prev`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+/// Where a save file is copied to before it's overwritten, for [`revert`].
+pub fn prev_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".prev")
+}
+
+/// Copy the file, as it is before saving, to its [`prev_path`]. A file that doesn't exist yet
+/// leaves no previous one, rather than an older one.
+fn keep_previous(path: &Path) -> Result<(), PersistError> {
+    let prev = prev_path(path);
+
+    // it may be a link to the file, see `keep_unchanged`, which copying over would write through
+    if prev.exists() {
+        std::fs::remove_file(&prev).map_err(PersistError::io(&prev))?;
+    }
+
+    if path.exists() {
+        std::fs::copy(path, &prev).map_err(PersistError::io(&prev))?;
+    }
+
+    Ok(())
+}
+
+/// [`keep_previous`] for a file the save leaves as it is, without copying all of it: the
+/// previous one is a hard link to it, or a copy where links aren't supported.
+fn keep_unchanged(path: &Path) -> Result<(), PersistError> {
+    let prev = prev_path(path);
+
+    if prev.exists() {
+        std::fs::remove_file(&prev).map_err(PersistError::io(&prev))?;
+    }
+
+    if path.exists() && std::fs::hard_link(path, &prev).is_err() {
+        std::fs::copy(path, &prev).map_err(PersistError::io(&prev))?;
+    }
+
+    Ok(())
+}
+
+/// Swap the save file at `path` with the one from before the last save, along with the binary
+/// save file's journal. Reverting again undoes the revert.
+pub fn revert(path: &Path) -> anyhow::Result<()> {
+    if !prev_path(path).exists() {
+        return Err(anyhow::anyhow!(
+            "there's no previous save file to revert to: {}",
+            prev_path(path).display()
+        ));
+    }
+
+    swap_with_previous(path)?;
+    swap_with_previous(&with_suffix(path, ".journal"))?;
+
+    debug!("reverted {} to before the last save", path.display());
+
+    Ok(())
+}
+
+fn swap_with_previous(path: &Path) -> Result<(), PersistError> {
+    let prev = prev_path(path);
+    let swapping = with_suffix(path, ".swap");
+
+    match (path.exists(), prev.exists()) {
+        (true, true) => {
+            std::fs::rename(path, &swapping).map_err(PersistError::io(path))?;
+            std::fs::rename(&prev, path).map_err(PersistError::io(&prev))?;
+            std::fs::rename(&swapping, &prev).map_err(PersistError::io(&swapping))?;
+        }
+        (true, false) => std::fs::rename(path, &prev).map_err(PersistError::io(path))?,
+        (false, true) => std::fs::rename(&prev, path).map_err(PersistError::io(&prev))?,
+        (false, false) => {}
+    }
+
+    Ok(())
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+fn gzip(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish().context("failed to compress save-file")
+}
+
+/// Neither of the save-file formats can start with the gzip magic bytes, so this tells
+/// compressed files apart from ones saved before compression was turned on.
+fn decompress_if_gzipped(data: Vec<u8>) -> Result<Vec<u8>, PersistError> {
+    if !data.starts_with(&GZIP_MAGIC) {
+        return Ok(data);
+    }
+
+    let mut decompressed = vec![];
+    GzDecoder::new(&data[..])
+        .read_to_end(&mut decompressed)
+        .map_err(|err| PersistError::deserialize(format!("failed to decompress: {err}")))?;
+
+    Ok(decompressed)
+}
+
+/// Where the save files are kept: `$MYND_DATA_DIR` if it's set, otherwise `$HOME/mynd`.
+pub fn data_dir() -> Result<PathBuf, PersistError> {
+    data_dir_from(std::env::var_os("MYND_DATA_DIR"), std::env::var_os("HOME"))
+}
+
+fn data_dir_from(
+    data_dir: Option<OsString>,
+    home: Option<OsString>,
+) -> Result<PathBuf, PersistError> {
     const DIR_NAME: &str = "mynd";
 
-    let get_dir_path = std::env::var("HOME")
-        .context("failed to read $HOME var")
-        .map(|path| -> PathBuf { Path::new(&path).join(DIR_NAME) });
+    if let Some(dir) = data_dir.filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+
+    match home.filter(|h| !h.is_empty()) {
+        Some(home) => Ok(Path::new(&home).join(DIR_NAME)),
+        None => Err(PersistError::Config(
+            "$HOME isn't set, so there's nowhere to keep todos; \
+            set $MYND_DATA_DIR to the directory to keep them in"
+                .to_string(),
+        )),
+    }
+}
+
+const JSON_SAVE_FILE: &str = "todo.json";
+const BINARY_SAVE_FILE: &str = "todo.bin";
+
+/// The directory that marks a project as having its own todos, like `.git`.
+const LOCAL_DIR: &str = ".mynd";
+
+/// A save file to use instead of the one in the data directory, see [`use_save_file`].
+static SAVE_FILE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Save todos as json at `path` from here on, for the rest of the process, e.g. a project's
+/// own. Only the first call has any effect.
+pub fn use_save_file(path: PathBuf) {
+    let _ = SAVE_FILE_OVERRIDE.set(path);
+}
+
+/// The save file of the project `from` is in: `.mynd/todo.json` in the nearest directory, from
+/// `from` up, that has a `.mynd` directory.
+pub fn find_local_save_file(from: &Path) -> Option<PathBuf> {
+    from.ancestors()
+        .map(|dir| dir.join(LOCAL_DIR))
+        .find(|dir| dir.is_dir())
+        .map(|dir| dir.join(JSON_SAVE_FILE))
+}
+
+/// A warning for when the save file of the format that isn't configured has todos in it, since
+/// they'd seem lost otherwise.
+fn unused_save_file_warning(dir: &Path, configured: &SaveFileFormat) -> Option<String> {
+    let (other, format, configured_format) = match configured {
+        SaveFileFormat::Json => (dir.join(BINARY_SAVE_FILE), "binary", "json"),
+        SaveFileFormat::Binary => (dir.join(JSON_SAVE_FILE), "json", "binary"),
+    };
+    if !other.is_file() {
+        return None;
+    }
 
-    let savefilepath = get_dir_path
+    let todos = match configured {
+        SaveFileFormat::Json => binary::TodosBin::at(other.clone()).get_all_todos(),
+        SaveFileFormat::Binary => jsonfile::TodosJsonDB::at(other.clone()).get_all_todos(),
+    };
+    let count = match todos {
+        Ok(todos) if todos.is_empty() => return None,
+        Ok(todos) => todos.len().to_string(),
+        Err(_) => "unreadable".to_string(),
+    };
+
+    Some(format!(
+        "{} has {count} todos that aren't shown, since the save file is configured to be {configured_format}; \
+        `mynd import {}` to bring them in, or `mynd config set -f {format}` to use it instead",
+        other.display(),
+        other.display(),
+    ))
+}
+
+/// Where todos are saved in the given format, creating the data directory if need be.
+pub fn save_file_path(format: &SaveFileFormat) -> Result<PathBuf, PersistError> {
+    if let Some(path) = SAVE_FILE_OVERRIDE.get() {
+        return Ok(path.clone());
+    }
+    save_file_path_in(data_dir(), format)
+}
+
+fn save_file_path_in(
+    dir: Result<PathBuf, PersistError>,
+    format: &SaveFileFormat,
+) -> Result<PathBuf, PersistError> {
+    let filename = match format {
+        SaveFileFormat::Json => JSON_SAVE_FILE,
+        SaveFileFormat::Binary => BINARY_SAVE_FILE,
+    };
+    savefilename_in(dir, filename)
+}
+
+fn get_or_create_savefilename(filename: &str) -> Result<PathBuf, PersistError> {
+    savefilename_in(data_dir(), filename)
+}
+
+fn savefilename_in(
+    dir: Result<PathBuf, PersistError>,
+    filename: &str,
+) -> Result<PathBuf, PersistError> {
+    let savefilepath = dir
         .and_then(|dir_path| {
-            eprintln!(
-                "[INFO] resolving mynd save directory as: {}",
-                dir_path.display()
-            );
+            debug!("resolving mynd save directory as: {}", dir_path.display());
 
             if !dir_path.is_dir() {
-                return std::fs::create_dir(&dir_path)
-                    .context("failed to create a 'mynd' directory")
+                return std::fs::create_dir_all(&dir_path)
+                    .map_err(PersistError::io(&dir_path))
                     .map(|_| dir_path);
             }
 
@@ -0,0 +1,191 @@
+use anyhow::anyhow;
+use clap::Args;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Server};
+use todo::{persist::TodosDatabase, Todos};
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Port to listen on, on localhost.
+    #[arg(short, long, default_value_t = 7878)]
+    port: u16,
+}
+
+impl ServeArgs {
+    pub fn handle(self) -> anyhow::Result<()> {
+        let todos = Todos::load_up_with_persistor();
+
+        let server = Server::http(("127.0.0.1", self.port))
+            .map_err(|err| anyhow!("{err}").context("failed to start the http server"))?;
+
+        eprintln!("[INFO] serving todos on http://127.0.0.1:{}", self.port);
+
+        for request in server.incoming_requests() {
+            // Pick up changes made by the cli or gui since the last request.
+            if let Err(err) = todos.reload() {
+                eprintln!("[ERROR] failed to reload todos: {err:#}");
+            }
+
+            if let Err(err) = respond(&todos, request) {
+                eprintln!("[ERROR] failed to respond to a request: {err:#}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn respond<DB: TodosDatabase>(todos: &Todos<DB>, mut request: Request) -> anyhow::Result<()> {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body)?;
+
+    let response = route(todos, request.method(), request.url(), &body);
+
+    let content_type = Header::from_bytes("Content-Type", "application/json")
+        .expect("a valid content type header");
+    request.respond(
+        tiny_http::Response::from_string(response.body.to_string())
+            .with_status_code(response.status)
+            .with_header(content_type),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn new(status: u16, body: Value) -> Self {
+        Self { status, body }
+    }
+
+    fn error(status: u16, err: anyhow::Error) -> Self {
+        Self::new(status, json!({ "error": format!("{err:#}") }))
+    }
+
+    fn from_result(status: u16, result: anyhow::Result<Value>) -> Self {
+        result.map_or_else(|err| Self::error(500, err), |body| Self::new(status, body))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NewTodo {
+    message: String,
+}
+
+/// Handles the API:
+///   GET /todos
+///   POST /todos             {"message": "..."}
+///   DELETE /todos/:id
+///   POST /todos/:id/done
+fn route<DB: TodosDatabase>(todos: &Todos<DB>, method: &Method, url: &str, body: &str) -> Response {
+    let path = url.split('?').next().unwrap_or_default();
+    let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["todos"]) => Response::from_result(200, todos.get_all().map(|t| json!(t))),
+        (Method::Post, ["todos"]) => {
+            let message = match serde_json::from_str::<NewTodo>(body) {
+                Ok(new) => new.message,
+                Err(err) => return Response::error(400, err.into()),
+            };
+            let todo = match todos.add_message(&message) {
+                Ok(todo) => todo,
+                Err(err) => return Response::error(400, err),
+            };
+            Response::from_result(201, todos.flush().map(|_| json!(todo)))
+        }
+        (Method::Delete, ["todos", id]) => {
+            if let Err(err) = todos.get(id) {
+                return Response::error(404, err);
+            }
+            Response::from_result(
+                200,
+                todos
+                    .remove(id)
+                    .and_then(|_| todos.flush())
+                    .map(|_| json!({ "id": id })),
+            )
+        }
+        (Method::Post, ["todos", id, "done"]) => {
+            if let Err(err) = todos.get(id) {
+                return Response::error(404, err);
+            }
+            Response::from_result(
+                200,
+                todos
+                    .set_done(id, true)
+                    .and_then(|_| todos.flush())
+                    .and_then(|_| todos.get(id))
+                    .map(|t| json!(t)),
+            )
+        }
+        _ => Response::error(404, anyhow!("no such route: {method} {path}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tiny_http::Method;
+    use todo::{Todo, Todos};
+
+    use super::route;
+
+    #[test]
+    fn crud_over_http() {
+        let todos = Todos::new_inmemory();
+
+        let created = route(&todos, &Method::Post, "/todos", r#"{"message": "one"}"#);
+        assert_eq!(created.status, 201);
+        let todo: Todo = serde_json::from_value(created.body).unwrap();
+        assert_eq!(todo.message, "one");
+
+        let listed = route(&todos, &Method::Get, "/todos", "");
+        assert_eq!(listed.status, 200);
+        assert_eq!(listed.body, json!([todo]));
+
+        let done = route(
+            &todos,
+            &Method::Post,
+            &format!("/todos/{}/done", todo.id.0),
+            "",
+        );
+        assert_eq!(done.status, 200);
+        assert_eq!(done.body["done"], json!(true));
+
+        let deleted = route(
+            &todos,
+            &Method::Delete,
+            &format!("/todos/{}", todo.id.0),
+            "",
+        );
+        assert_eq!(deleted.status, 200);
+        assert_eq!(route(&todos, &Method::Get, "/todos", "").body, json!([]));
+    }
+
+    #[test]
+    fn bad_requests() {
+        let todos = Todos::new_inmemory();
+
+        assert_eq!(route(&todos, &Method::Post, "/todos", "nope").status, 400);
+        assert_eq!(
+            route(&todos, &Method::Post, "/todos", r#"{"message": ""}"#).status,
+            400
+        );
+        assert_eq!(
+            route(&todos, &Method::Delete, "/todos/missing", "").status,
+            404
+        );
+        assert_eq!(
+            route(&todos, &Method::Post, "/todos/missing/done", "").status,
+            404
+        );
+        assert_eq!(route(&todos, &Method::Get, "/nothing", "").status, 404);
+    }
+}
@@ -1,6 +1,7 @@
 import { writable } from "svelte/store";
 import { erroneous, handleError } from "./utils";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { addToast } from "./toasts/store";
 
 export const listulelement = writable<HTMLUListElement | null>(null);
@@ -12,8 +13,24 @@ export type Todo = {
   done?: boolean;
 };
 
+export type Stats = {
+  total: number;
+  done: number;
+  pending: number;
+  oldest: Todo | null;
+  newest: Todo | null;
+};
+
+export type TodosWithStats = {
+  todos: Todo[];
+  total: number;
+  done: number;
+};
+
 export const todos = writable<Todo[]>([]);
 
+listen<Todo[]>("todos-changed", (event) => todos.set(event.payload));
+
 export async function load() {
   await erroneous<Todo[]>(invoke("load"))({
     success: (data) => {
@@ -24,6 +41,18 @@ export async function load() {
   });
 }
 
+export async function loadWithStats() {
+  let stats: Omit<TodosWithStats, "todos"> | undefined;
+  await erroneous<TodosWithStats>(invoke("load_with_stats"))({
+    success: ({ todos: list, total, done }) => {
+      todos.set(list);
+      stats = { total, done };
+    },
+    error: handleError,
+  });
+  return stats;
+}
+
 export async function addTodo(item: string) {
   if (!item) return;
 
@@ -88,6 +117,43 @@ export async function moveBelow(sourceTodoId: string, targetTodoId: string) {
   });
 }
 
+export async function reorder(id: string, index: number) {
+  await erroneous<Todo[]>(invoke("reorder", { id, index }))({
+    success: (data) => todos.set(data),
+    error: handleError,
+  });
+}
+
+export async function moveManyToTop(ids: string[]) {
+  await erroneous<Todo[]>(invoke("move_many_to_top", { ids }))({
+    success: (data) => todos.set(data),
+    error: handleError,
+  });
+}
+
+export async function sinkDone() {
+  await erroneous<Todo[]>(invoke("sink_done"))({
+    success: (data) => todos.set(data),
+    error: handleError,
+  });
+}
+
+export async function setOrder(ids: string[]) {
+  await erroneous<Todo[]>(invoke("set_order", { ids }))({
+    success: (data) => todos.set(data),
+    error: handleError,
+  });
+}
+
+export async function getStats() {
+  let stats: Stats | undefined;
+  await erroneous<Stats>(invoke("stats"))({
+    success: (data) => (stats = data),
+    error: handleError,
+  });
+  return stats;
+}
+
 export async function deleteTodo(id: string) {
   await erroneous<Todo[]>(invoke("delete", { id }))({
     success: (data) => {